@@ -1,17 +1,55 @@
 extern crate image;
 use gl_obj::*;
 
-pub fn load_image(fname: &str) -> Texture {
-	let (size, data) = load_image_data(fname);
-	Texture::new2d(gl::SRGB8_ALPHA8, size).sub_image2d(0, 0, 0, size.0, size.1, gl::RGBA, gl::UNSIGNED_BYTE, &data)
+/// Load an image file into an sRGB texture.
+pub fn load_image(fname: &str) -> Result<Texture, String> {
+	let (size, data) = load_image_data(fname)?;
+	Ok(Texture::new2d(gl::SRGB8_ALPHA8, size).sub_image2d(0, 0, 0, size.0, size.1, gl::RGBA, gl::UNSIGNED_BYTE, &data))
 }
 
-pub fn load_image_data(fname: &str) -> (uvec2, Vec<[u8; 4]>) {
-	let src = image::io::Reader::open(fname).expect("open image").decode().expect("decode image").into_rgba();
+/// Load an image file as gray levels between 0 (black) and 1 (white),
+/// row by row, resized to `size` if needed.
+pub fn load_gray_image(fname: &str, size: uvec2) -> Result<Vec<f32>, String> {
+	let src = image::io::Reader::open(fname) //
+		.map_err(|e| format!("open image {}: {}", fname, e))?
+		.decode()
+		.map_err(|e| format!("decode image {}: {}", fname, e))?
+		.into_luma();
+	let src = if src.dimensions() == (size.0, size.1) {
+		src
+	} else {
+		image::imageops::resize(&src, size.0, size.1, image::imageops::FilterType::Triangle)
+	};
+	Ok(src.pixels().map(|c| (c[0] as f32) / 255.0).collect())
+}
+
+/// Load an image file as RGB colors with components between 0 and 1,
+/// row by row, resized to `size` if needed.
+pub fn load_rgb_image(fname: &str, size: uvec2) -> Result<Vec<[f32; 3]>, String> {
+	let src = image::io::Reader::open(fname) //
+		.map_err(|e| format!("open image {}: {}", fname, e))?
+		.decode()
+		.map_err(|e| format!("decode image {}: {}", fname, e))?
+		.into_rgb();
+	let src = if src.dimensions() == (size.0, size.1) {
+		src
+	} else {
+		image::imageops::resize(&src, size.0, size.1, image::imageops::FilterType::Triangle)
+	};
+	Ok(src.pixels().map(|c| [(c[0] as f32) / 255.0, (c[1] as f32) / 255.0, (c[2] as f32) / 255.0]).collect())
+}
+
+/// Load an image file as RGBA pixels, row by row.
+pub fn load_image_data(fname: &str) -> Result<(uvec2, Vec<[u8; 4]>), String> {
+	let src = image::io::Reader::open(fname) //
+		.map_err(|e| format!("open image {}: {}", fname, e))?
+		.decode()
+		.map_err(|e| format!("decode image {}: {}", fname, e))?
+		.into_rgba();
 	let size = uvec2(src.width(), src.height());
 	let mut data = Vec::with_capacity((size.0 as usize) * (size.1 as usize));
 	for c in src.pixels() {
 		data.push([c[0], c[1], c[2], c[3]])
 	}
-	(size, data)
+	Ok((size, data))
 }
@@ -38,6 +38,35 @@ impl Buffer {
 		}
 	}
 
+	/// Updates a subset of the buffer's data store, starting at element `offset`.
+	/// The buffer must have been created with `DYNAMIC_STORAGE_BIT`.
+	/// http://docs.gl/gl4/glBufferSubData
+	pub fn sub_data<T>(&self, offset: usize, data: &[T])
+	where
+		T: Sized + Copy + 'static,
+	{
+		debug_assert!(TypeId::of::<T>() == self.typeid);
+		debug_assert!(offset + data.len() <= self.len);
+		glNamedBufferSubData(self.handle, offset * size_of::<T>(), data);
+	}
+
+	/// Reads part of the buffer's data store, starting at element `offset`, into `data`.
+	/// http://docs.gl/gl4/glGetBufferSubData
+	pub fn get_sub_data<T>(&self, offset: usize, data: &mut [T])
+	where
+		T: Sized + Copy + 'static,
+	{
+		debug_assert!(TypeId::of::<T>() == self.typeid);
+		debug_assert!(offset + data.len() <= self.len);
+		glGetNamedBufferSubData(self.handle, offset * size_of::<T>(), data);
+	}
+
+	/// Bind the buffer to an indexed target, like a shader storage block binding.
+	/// http://docs.gl/gl4/glBindBufferBase
+	pub fn bind_base(&self, target: GLenum, index: u32) {
+		glBindBufferBase(target, index, self.handle)
+	}
+
 	pub fn stride(&self) -> i32 {
 		self.stride as i32
 	}
@@ -46,6 +75,10 @@ impl Buffer {
 		self.len
 	}
 
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
 	pub fn bytes(&self) -> usize {
 		self.len * (self.stride as usize)
 	}
@@ -57,8 +90,8 @@ impl Buffer {
 	//}
 }
 
-impl Into<GLuint> for Buffer {
-	fn into(self) -> GLuint {
-		self.handle
+impl From<Buffer> for GLuint {
+	fn from(val: Buffer) -> Self {
+		val.handle
 	}
 }
@@ -0,0 +1,85 @@
+use gl_safe::*;
+
+/// Information about the GL implementation behind the current context.
+pub struct GlInfo {
+	pub vendor: String,
+	pub renderer: String,
+	pub version: String,
+	pub glsl_version: String,
+	pub major: i32,
+	pub minor: i32,
+}
+
+impl GlInfo {
+	/// Query the current context, with calls supported by any GL version.
+	pub fn query() -> Self {
+		let version = glGetString(gl::VERSION);
+		let (major, minor) = parse_version(&version);
+		Self {
+			vendor: glGetString(gl::VENDOR),
+			renderer: glGetString(gl::RENDERER),
+			glsl_version: glGetString(gl::SHADING_LANGUAGE_VERSION),
+			version,
+			major,
+			minor,
+		}
+	}
+
+	/// Error unless the context supports at least GL version `major.minor`.
+	pub fn require_version(&self, major: i32, minor: i32) -> Result<(), String> {
+		if (self.major, self.minor) < (major, minor) {
+			Err(format!(
+				"OpenGL {}.{} required, but {} ({}) only supports {}.{}",
+				major, minor, self.renderer, self.vendor, self.major, self.minor
+			))
+		} else {
+			Ok(())
+		}
+	}
+}
+
+/// Implementation limits relevant to compute shaders.
+pub struct GlLimits {
+	pub max_texture_size: i32,
+	pub max_image_units: i32,
+	pub max_compute_work_group_count: [i32; 3],
+	pub max_compute_work_group_size: [i32; 3],
+	pub max_compute_work_group_invocations: i32,
+}
+
+impl GlLimits {
+	/// Query the current context, which must support GL 4.3 or later
+	/// (check with `GlInfo::require_version` first).
+	pub fn query() -> Self {
+		let indexed = |pname| [glGetIntegeri_v(pname, 0), glGetIntegeri_v(pname, 1), glGetIntegeri_v(pname, 2)];
+		Self {
+			max_texture_size: glGetIntegerv(gl::MAX_TEXTURE_SIZE),
+			max_image_units: glGetIntegerv(gl::MAX_IMAGE_UNITS),
+			max_compute_work_group_count: indexed(gl::MAX_COMPUTE_WORK_GROUP_COUNT),
+			max_compute_work_group_size: indexed(gl::MAX_COMPUTE_WORK_GROUP_SIZE),
+			max_compute_work_group_invocations: glGetIntegerv(gl::MAX_COMPUTE_WORK_GROUP_INVOCATIONS),
+		}
+	}
+}
+
+/// Major and minor version from a GL version string like "4.6 (Core Profile) Mesa 21.2"
+/// or "OpenGL ES 3.2", (0, 0) if there is none.
+fn parse_version(version: &str) -> (i32, i32) {
+	let number = version.split_whitespace().find(|w| w.starts_with(|c: char| c.is_ascii_digit())).unwrap_or("");
+	let mut parts = number.split('.').map(|p| p.parse::<i32>().unwrap_or(0));
+	(parts.next().unwrap_or(0), parts.next().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_versions() {
+		assert_eq!(parse_version("4.6 (Core Profile) Mesa 21.2.6"), (4, 6));
+		assert_eq!(parse_version("3.1.0 NVIDIA 470.86"), (3, 1));
+		assert_eq!(parse_version("OpenGL ES 3.2 Mesa 21.2.6"), (3, 2));
+		assert_eq!(parse_version("2.1"), (2, 1));
+		assert_eq!(parse_version(""), (0, 0));
+	}
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::too_many_arguments)]
+
 extern crate gl_safe;
 extern crate gl_vec;
 
@@ -5,6 +7,7 @@ pub use gl_vec::*;
 
 mod buffer;
 mod consts;
+mod info;
 mod program;
 mod shader;
 mod texture;
@@ -12,7 +15,9 @@ mod util;
 mod vertexarray;
 
 pub use buffer::*;
+#[allow(unused_imports)] // consts is empty for now, the re-export stays part of the API
 pub use consts::*;
+pub use info::*;
 pub use program::*;
 pub use shader::*;
 pub use texture::*;
@@ -12,11 +12,16 @@ impl Program {
 	}
 
 	pub fn new(shaders: &[Shader]) -> Self {
+		Self::try_new(shaders).expect("link program")
+	}
+
+	/// Like `new`, but returns the info log if linking fails.
+	pub fn try_new(shaders: &[Shader]) -> Result<Self, String> {
 		let p = Self::create();
 		for s in shaders {
 			p.attach_shader(*s);
 		}
-		p.link().expect("link program")
+		p.link()
 	}
 
 	/// Attaches a shader object to a program object.
@@ -28,7 +33,6 @@ impl Program {
 
 	/// Links a program object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glLinkProgram.xhtml
-	#[must_use]
 	pub fn link(self) -> Result<Self, String> {
 		glLinkProgram(self.0);
 		let status = glGetProgramiv(self.0, gl::LINK_STATUS, 1)[0];
@@ -163,6 +167,18 @@ impl Program {
 		self
 	}
 
+	pub fn set2f(self, attrib: &str, v0: f32, v1: f32) -> Self {
+		let loc = self.uniform_location(attrib);
+		self.uniform2f(loc, v0, v1);
+		self
+	}
+
+	pub fn set3f(self, attrib: &str, v0: f32, v1: f32, v2: f32) -> Self {
+		let loc = self.uniform_location(attrib);
+		self.uniform3f(loc, v0, v1, v2);
+		self
+	}
+
 	pub fn set2i(self, attrib: &str, v0: i32, v1: i32) -> Self {
 		let loc = self.uniform_location(attrib);
 		self.uniform2i(loc, v0, v1);
@@ -176,8 +192,8 @@ impl Program {
 	}
 }
 
-impl Into<GLuint> for Program {
-	fn into(self) -> GLuint {
-		self.0
+impl From<Program> for GLuint {
+	fn from(val: Program) -> Self {
+		val.0
 	}
 }
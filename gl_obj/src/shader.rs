@@ -11,6 +11,11 @@ impl Shader {
 		Self(glCreateShader(shader_type))
 	}
 
+	/// Create and compile a shader, returning the info log if compilation fails.
+	pub fn try_new(shader_type: GLenum, src: &str) -> Result<Self, String> {
+		Self::create(shader_type).source(src).compile()
+	}
+
 	pub fn new_vert(src: &str) -> Self {
 		Self::create(gl::VERTEX_SHADER).source(src).compile().expect("compile vertex sharder")
 	}
@@ -32,7 +37,6 @@ impl Shader {
 
 	/// Compiles a shader object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glCompileShader.xhtml
-	#[must_use]
 	pub fn compile(self) -> Result<Self, String> {
 		glCompileShader(self.0);
 		let status = self.get_iv(gl::COMPILE_STATUS);
@@ -58,8 +62,8 @@ impl Shader {
 	}
 }
 
-impl Into<GLuint> for Shader {
-	fn into(self) -> GLuint {
-		self.0
+impl From<Shader> for GLuint {
+	fn from(val: Shader) -> Self {
+		val.0
 	}
 }
@@ -21,6 +21,12 @@ impl Texture {
 		}
 	}
 
+	/// Delete the texture, it must not be used afterwards.
+	/// http://docs.gl/gl4/glDeleteTextures
+	pub fn delete(self) {
+		glDeleteTexture(self.handle)
+	}
+
 	pub fn new1d(internalformat: GLenum, width: u32) -> Self {
 		Self::create(gl::TEXTURE_1D).storage1d(1, internalformat, width)
 	}
@@ -80,6 +86,10 @@ impl Texture {
 		self.size
 	}
 
+	pub fn dimensionality(&self) -> u8 {
+		self.dimensionality
+	}
+
 	pub fn bind_image_unit(&self, unit: u32, access: GLenum) {
 		glBindImageTexture(unit, self.handle, 0, false, 0, access, self.internalformat());
 	}
@@ -130,6 +140,25 @@ impl Texture {
 		self
 	}
 
+	/// Read a texture image into `pixels`, one element per pixel.
+	/// http://docs.gl/gl4/glGetTextureImage
+	pub fn get_image<T>(&self, level: u32, format: GLenum, typ: GLenum, pixels: &mut [T])
+	where
+		T: Sized + Copy + 'static,
+	{
+		glGetTextureImage(self.handle, level as i32, format, typ, pixels);
+	}
+
+	/// Fills all of a texture image with a constant value.
+	/// http://docs.gl/gl4/glClearTexImage
+	pub fn clear_image<T>(self, level: u32, format: GLenum, typ: GLenum, data: &T) -> Self
+	where
+		T: Sized + Copy + 'static,
+	{
+		glClearTexImage(self.handle, level as i32, format, typ, data);
+		self
+	}
+
 	/// Set texture parameters.
 	/// http://docs.gl/gl4/glTexParameter
 	pub fn parameterf(self, pname: GLenum, param: f32) -> Self {
@@ -176,6 +205,12 @@ impl Texture {
 			.parameteri(gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32)
 	}
 
+	pub fn repeat(self) -> Self {
+		self //
+			.parameteri(gl::TEXTURE_WRAP_S, gl::REPEAT as i32)
+			.parameteri(gl::TEXTURE_WRAP_T, gl::REPEAT as i32)
+	}
+
 	pub fn mirrored_repeat(self) -> Self {
 		self //
 			.parameteri(gl::TEXTURE_WRAP_S, gl::MIRRORED_REPEAT as i32)
@@ -183,8 +218,8 @@ impl Texture {
 	}
 }
 
-impl Into<GLuint> for Texture {
-	fn into(self) -> GLuint {
-		self.handle
+impl From<Texture> for GLuint {
+	fn from(val: Texture) -> Self {
+		val.handle
 	}
 }
@@ -62,8 +62,8 @@ impl VertexArray {
 	}
 }
 
-impl Into<GLuint> for VertexArray {
-	fn into(self) -> GLuint {
-		self.0
+impl From<VertexArray> for GLuint {
+	fn from(val: VertexArray) -> Self {
+		val.0
 	}
 }
@@ -3,7 +3,7 @@ use gl::types::*;
 use std::mem;
 
 pub fn image_size<T>(dim: &[i32], format: GLenum, typ: GLenum, pixels: &[T]) {
-	let have_pix = dim.iter().map(|x| *x as usize).fold(1, |a, b| a * b);
+	let have_pix = dim.iter().map(|x| *x as usize).product::<usize>();
 	if have_pix != pixels.len() {
 		panic!("image size: want {} pixels ({:?}), have: {}", have_pix, dim, pixels.len());
 	}
@@ -21,10 +21,10 @@ pub fn image_size<T>(dim: &[i32], format: GLenum, typ: GLenum, pixels: &[T]) {
 
 fn format_num_el(format: GLenum) -> usize {
 	match format {
-		gl::RED => 1,
-		gl::RG => 2,
-		gl::RGB => 3,
-		gl::RGBA => 4,
+		gl::RED | gl::RED_INTEGER => 1,
+		gl::RG | gl::RG_INTEGER => 2,
+		gl::RGB | gl::RGB_INTEGER => 3,
+		gl::RGBA | gl::RGBA_INTEGER => 4,
 		x => panic!("unknown pixel format: {}", x),
 	}
 }
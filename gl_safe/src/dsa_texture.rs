@@ -12,6 +12,14 @@ pub fn glCreateTexture(target: GLenum) -> GLuint {
 	result
 }
 
+/// Delete a (single) named texture.
+/// http://docs.gl/gl4/glDeleteTextures
+#[allow(non_snake_case)]
+pub fn glDeleteTexture(texture: GLuint) {
+	unsafe { gl::DeleteTextures(1, &texture) };
+	check::gl_error();
+}
+
 /// Simultaneously specify storage for all levels of a one-dimensional texture.
 /// http://docs.gl/gl4/glTexStorage1D
 #[allow(non_snake_case)]
@@ -44,7 +52,7 @@ where
 	T: Sized + Copy + 'static,
 {
 	check::image_size(&[width], format, typ, pixels);
-	unsafe { gl::TextureSubImage1D(texture, level, xoffset, width, format, typ, mem::transmute(&pixels[0])) };
+	unsafe { gl::TextureSubImage1D(texture, level, xoffset, width, format, typ, pixels.as_ptr() as *const _) };
 	check::gl_error()
 }
 
@@ -56,7 +64,7 @@ where
 	T: Sized + Copy + 'static,
 {
 	check::image_size(&[width, height], format, typ, pixels);
-	unsafe { gl::TextureSubImage2D(texture, level, xoffset, yoffset, width, height, format, typ, mem::transmute(&pixels[0])) };
+	unsafe { gl::TextureSubImage2D(texture, level, xoffset, yoffset, width, height, format, typ, pixels.as_ptr() as *const _) };
 	check::gl_error()
 }
 
@@ -68,7 +76,7 @@ where
 	T: Sized + Copy + 'static,
 {
 	check::image_size(&[width, height, depth], format, typ, pixels);
-	unsafe { gl::TextureSubImage3D(texture, level, xoffset, yoffset, zoffset, width, height, depth, format, typ, mem::transmute(&pixels[0])) };
+	unsafe { gl::TextureSubImage3D(texture, level, xoffset, yoffset, zoffset, width, height, depth, format, typ, pixels.as_ptr() as *const _) };
 	check::gl_error()
 }
 
@@ -104,6 +112,30 @@ pub fn glTextureParameteriv(texture: GLuint, pname: GLenum, param: &[i32]) {
 	check::gl_error()
 }
 
+/// Fills all of a texture image with a constant value.
+/// http://docs.gl/gl4/glClearTexImage
+#[allow(non_snake_case)]
+pub fn glClearTexImage<T>(texture: GLuint, level: i32, format: GLenum, typ: GLenum, data: &T)
+where
+	T: Sized + Copy + 'static,
+{
+	check::image_size(&[1], format, typ, std::slice::from_ref(data));
+	unsafe { gl::ClearTexImage(texture, level, format, typ, data as *const T as *const _) };
+	check::gl_error()
+}
+
+/// Return a texture image into `pixels`, which must be large enough to hold it.
+/// http://docs.gl/gl4/glGetTextureImage
+#[allow(non_snake_case)]
+pub fn glGetTextureImage<T>(texture: GLuint, level: i32, format: GLenum, typ: GLenum, pixels: &mut [T])
+where
+	T: Sized + Copy + 'static,
+{
+	let bytes = mem::size_of_val(pixels);
+	unsafe { gl::GetTextureImage(texture, level, format, typ, bytes as i32, pixels.as_mut_ptr() as *mut _) };
+	check::gl_error()
+}
+
 // //pub fn glTextureParameterIiv (texture: GLuint, pname: GLenum, const int *params){}
 // //pub fn glTextureParameterIuiv(texture: GLuint, pname: GLenum, const uint *params){}
 
@@ -1,7 +1,7 @@
 use super::*;
 use gl::types::*;
 
-/// https://www.khronos.org/registry/OpenGL/extensions/ARB/ARB_direct_state_access.txt
+// https://www.khronos.org/registry/OpenGL/extensions/ARB/ARB_direct_state_access.txt
 
 /// Create a vertex array object.
 /// http://docs.gl/gl4/glCreateVertexArrays
@@ -19,8 +19,40 @@ pub fn glNamedBufferStorage<T>(buffer: GLuint, data: &[T], flags: GLbitfield)
 where
 	T: Sized + Copy + 'static,
 {
-	let bytes = data.len() * mem::size_of::<T>();
-	unsafe { gl::NamedBufferStorage(buffer, bytes as isize, mem::transmute(&data[0]), flags) }
+	let bytes = mem::size_of_val(data);
+	unsafe { gl::NamedBufferStorage(buffer, bytes as isize, data.as_ptr() as *const _, flags) }
+	check::gl_error();
+}
+
+/// Updates a subset of a buffer object's data store.
+/// http://docs.gl/gl4/glBufferSubData
+#[allow(non_snake_case)]
+pub fn glNamedBufferSubData<T>(buffer: GLuint, offset: usize, data: &[T])
+where
+	T: Sized + Copy + 'static,
+{
+	let bytes = mem::size_of_val(data);
+	unsafe { gl::NamedBufferSubData(buffer, offset as isize, bytes as isize, data.as_ptr() as *const _) }
+	check::gl_error();
+}
+
+/// Returns a subset of a buffer object's data store.
+/// http://docs.gl/gl4/glGetBufferSubData
+#[allow(non_snake_case)]
+pub fn glGetNamedBufferSubData<T>(buffer: GLuint, offset: usize, data: &mut [T])
+where
+	T: Sized + Copy + 'static,
+{
+	let bytes = mem::size_of_val(data);
+	unsafe { gl::GetNamedBufferSubData(buffer, offset as isize, bytes as isize, data.as_mut_ptr() as *mut _) }
+	check::gl_error();
+}
+
+/// Bind a buffer object to an indexed buffer target.
+/// http://docs.gl/gl4/glBindBufferBase
+#[allow(non_snake_case)]
+pub fn glBindBufferBase(target: GLenum, index: u32, buffer: GLuint) {
+	unsafe { gl::BindBufferBase(target, index, buffer) }
 	check::gl_error();
 }
 
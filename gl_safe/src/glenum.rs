@@ -1350,7 +1350,7 @@ pub fn to_str(v: GLenum) -> String {
 		// 		gl::ZERO_TO_ONE => "ZERO_TO_ONE",
 		_ => "",
 	};
-	if s == "" {
+	if s.is_empty() {
 		format!("GLenum{}", v)
 	} else {
 		s.into()
@@ -2,7 +2,7 @@ extern crate gl;
 
 use super::check;
 use gl::types::*;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::mem;
 use std::ptr;
 
@@ -42,7 +42,7 @@ where
 	T: Sized + Copy + 'static,
 {
 	let data = match data {
-		Some(slice) => unsafe { mem::transmute(&slice[0]) },
+		Some(slice) => slice.as_ptr() as *const _,
 		None => ptr::null(),
 	};
 	// TODO: check width * height == data.len()
@@ -100,6 +100,30 @@ pub fn glClear(mask: GLbitfield) {
 	check::gl_error()
 }
 
+/// Enable server-side GL capabilities.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glEnable.xhtml
+#[allow(non_snake_case)]
+pub fn glEnable(cap: GLenum) {
+	unsafe { gl::Enable(cap) };
+	check::gl_error()
+}
+
+/// Disable server-side GL capabilities.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glEnable.xhtml
+#[allow(non_snake_case)]
+pub fn glDisable(cap: GLenum) {
+	unsafe { gl::Disable(cap) };
+	check::gl_error()
+}
+
+/// Specify pixel arithmetic.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBlendFunc.xhtml
+#[allow(non_snake_case)]
+pub fn glBlendFunc(sfactor: GLenum, dfactor: GLenum) {
+	unsafe { gl::BlendFunc(sfactor, dfactor) };
+	check::gl_error()
+}
+
 /// Returns a parameter from a program object.
 /// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetProgram.xhtml.
 /// TODO: could be more than 1 !!!
@@ -107,17 +131,11 @@ pub fn glClear(mask: GLbitfield) {
 pub fn glGetProgramiv(program: GLuint, pname: GLenum, n: usize) -> Vec<i32> {
 	let mut safezone: [i32; 128] = [0xdeadb3f; 128];
 	unsafe { gl::GetProgramiv(program, pname, &mut safezone[0]) }
-	let mut result = Vec::with_capacity(n);
-	for i in 0..n {
-		result.push(safezone[i]);
-	}
-	for i in n..128 {
-		if safezone[i] != 0xdeadb3f {
-			panic!("glGetProgramiv: argument n ({}) too small.", n);
-		}
+	if safezone[n..].iter().any(|v| *v != 0xdeadb3f) {
+		panic!("glGetProgramiv: argument n ({}) too small.", n);
 	}
 	check::gl_error();
-	result
+	safezone[..n].to_vec()
 }
 
 /// Generate a (single) vertex array object name.
@@ -179,8 +197,8 @@ pub fn glBufferData<T>(target: GLenum, data: &[T], usage: GLenum)
 where
 	T: Sized + 'static,
 {
-	let size = (data.len() * mem::size_of::<T>()) as GLsizeiptr;
-	let data = unsafe { mem::transmute(&data[0]) };
+	let size = mem::size_of_val(data) as GLsizeiptr;
+	let data = data.as_ptr() as *const _;
 	unsafe { gl::BufferData(target, size, data, usage) };
 	check::gl_error();
 }
@@ -194,6 +212,38 @@ pub fn glVertexAttribPointer(index: GLuint, size: i32, typ: GLenum, normalized:
 	check::gl_error()
 }
 
+/// Return a string describing the current GL connection.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetString.xhtml
+#[allow(non_snake_case)]
+pub fn glGetString(name: GLenum) -> String {
+	let ptr = unsafe { gl::GetString(name) };
+	check::gl_error();
+	if ptr.is_null() {
+		return "".into();
+	}
+	unsafe { CStr::from_ptr(ptr as *const GLchar) }.to_string_lossy().into_owned()
+}
+
+/// Return the value of a simple state variable.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGet.xhtml
+#[allow(non_snake_case)]
+pub fn glGetIntegerv(pname: GLenum) -> i32 {
+	let mut data = 0;
+	unsafe { gl::GetIntegerv(pname, &mut data) };
+	check::gl_error();
+	data
+}
+
+/// Return the value of an indexed state variable.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGet.xhtml
+#[allow(non_snake_case)]
+pub fn glGetIntegeri_v(pname: GLenum, index: u32) -> i32 {
+	let mut data = 0;
+	unsafe { gl::GetIntegeri_v(pname, index, &mut data) };
+	check::gl_error();
+	data
+}
+
 /// return error information.
 /// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetError.xhtml
 #[allow(non_snake_case)]
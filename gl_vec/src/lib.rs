@@ -3,6 +3,7 @@ mod ivec;
 mod uvec;
 mod vec;
 
+#[allow(unused_imports)] // dvec is empty for now, the re-export stays part of the API
 pub use dvec::*;
 pub use ivec::*;
 pub use uvec::*;
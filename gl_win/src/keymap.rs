@@ -0,0 +1,117 @@
+/*
+	Key bindings: maps keys to named actions.
+
+	Bindings are read from plain text, one binding per line:
+
+		# key    action
+		Space    pause
+		Escape   quit
+
+	What an action name means is up to the application.
+*/
+use glutin::event::{ElementState, KeyboardInput, VirtualKeyCode};
+use std::collections::HashMap;
+use std::fs;
+
+#[derive(Clone, Default)]
+pub struct KeyMap {
+	bindings: HashMap<VirtualKeyCode, String>,
+}
+
+impl KeyMap {
+	/// An empty key map.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Parse bindings from text (see top of file for the format).
+	pub fn parse(text: &str) -> Result<Self, String> {
+		let mut m = Self::new();
+		m.extend(text)?;
+		Ok(m)
+	}
+
+	/// Add bindings from a file, overriding existing bindings for the same keys.
+	pub fn load(&mut self, fname: &str) -> Result<(), String> {
+		let text = fs::read_to_string(fname).map_err(|e| format!("{}: {}", fname, e))?;
+		self.extend(&text).map_err(|e| format!("{}: {}", fname, e))
+	}
+
+	/// Add bindings from text, overriding existing bindings for the same keys.
+	pub fn extend(&mut self, text: &str) -> Result<(), String> {
+		for (i, line) in text.lines().enumerate() {
+			let line = line.split('#').next().unwrap().trim();
+			if line.is_empty() {
+				continue;
+			}
+			let words: Vec<&str> = line.split_whitespace().collect();
+			if words.len() != 2 {
+				return Err(format!("line {}: want `key action`, have `{}`", i + 1, line));
+			}
+			let key = key_from_name(words[0]).ok_or(format!("line {}: unknown key `{}`", i + 1, words[0]))?;
+			self.bind(key, words[1]);
+		}
+		Ok(())
+	}
+
+	/// Bind `key` to `action`, replacing any previous binding for `key`.
+	pub fn bind(&mut self, key: VirtualKeyCode, action: &str) {
+		self.bindings.insert(key, action.into());
+	}
+
+	/// The action bound to `key`, if any.
+	pub fn get(&self, key: VirtualKeyCode) -> Option<&str> {
+		self.bindings.get(&key).map(|a| a.as_str())
+	}
+
+	/// The action triggered by a keyboard event.
+	/// Only key presses trigger actions, releases are ignored.
+	pub fn action(&self, input: KeyboardInput) -> Option<&str> {
+		match (input.state, input.virtual_keycode) {
+			(ElementState::Pressed, Some(key)) => self.get(key),
+			_ => None,
+		}
+	}
+
+	/// All bindings, sorted by action name.
+	pub fn bindings(&self) -> Vec<(&'static str, &str)> {
+		let mut b: Vec<_> = self.bindings.iter().map(|(k, a)| (key_name(*k), a.as_str())).collect();
+		b.sort_by(|x, y| (x.1, x.0).cmp(&(y.1, y.0)));
+		b
+	}
+}
+
+macro_rules! key_names {
+	($($key:ident),* $(,)?) => {
+		/// Key code for a key name like `A`, `Key1`, `Space` or `F1`
+		/// (the `VirtualKeyCode` variant name, case-insensitive).
+		pub fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+			$(
+				if name.eq_ignore_ascii_case(stringify!($key)) {
+					return Some(VirtualKeyCode::$key);
+				}
+			)*
+			None
+		}
+
+		/// Name of a key, as accepted by `key_from_name`.
+		pub fn key_name(key: VirtualKeyCode) -> &'static str {
+			match key {
+				$(VirtualKeyCode::$key => stringify!($key),)*
+				_ => "?",
+			}
+		}
+	};
+}
+
+key_names! {
+	Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0,
+	A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+	Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+	Insert, Home, Delete, End, PageDown, PageUp,
+	Left, Up, Right, Down,
+	Back, Return, Space, Tab,
+	Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+	Add, Subtract, Multiply, Divide, Decimal, NumpadEnter, NumpadEquals,
+	Apostrophe, Backslash, Comma, Equals, Grave, LBracket, Minus, Period, RBracket, Semicolon, Slash,
+}
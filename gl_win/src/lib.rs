@@ -4,7 +4,11 @@
 extern crate glutin;
 use std::sync::Arc;
 
-pub use glutin::event::{Event, WindowEvent};
+mod keymap;
+
+pub use keymap::*;
+
+pub use glutin::event::{ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, Touch, TouchPhase, VirtualKeyCode, WindowEvent};
 pub use glutin::event_loop::ControlFlow;
 
 pub type Window = glutin::ContextWrapper<glutin::PossiblyCurrent, glutin::window::Window>;
@@ -12,7 +16,8 @@ pub type EventLoop = glutin::event_loop::EventLoop<()>;
 
 /// Initialize the GL context
 /// and create a window and associated event loop.
-pub fn init_gl_window(w: u32, h: u32, title: &str) -> (Arc<Window>, EventLoop) {
+#[allow(clippy::arc_with_non_send_sync)]
+pub fn init_gl_window(w: u32, h: u32, title: &str) -> Result<(Arc<Window>, EventLoop), String> {
 	let size = glutin::dpi::LogicalSize::new(w, h); // ?
 	let event_loop = glutin::event_loop::EventLoop::new();
 	let window = glutin::window::WindowBuilder::new() //
@@ -22,10 +27,10 @@ pub fn init_gl_window(w: u32, h: u32, title: &str) -> (Arc<Window>, EventLoop) {
 	let gl_window = glutin::ContextBuilder::new() //
 		.with_vsync(true)
 		.build_windowed(window, &event_loop)
-		.unwrap();
-	let gl_window = unsafe { gl_window.make_current() }.unwrap();
+		.map_err(|e| format!("create OpenGL window: {}", e))?;
+	let gl_window = unsafe { gl_window.make_current() }.map_err(|(_, e)| format!("activate OpenGL context: {}", e))?;
 	gl::load_with(|symbol| gl_window.get_proc_address(symbol));
-	(Arc::new(gl_window), event_loop)
+	Ok((Arc::new(gl_window), event_loop))
 }
 
 /// Enter a continuous redraw loop, calling `draw` at VSync rate.
@@ -42,11 +47,9 @@ where
 				gl_window.swap_buffers().unwrap();
 				gl_window.window().request_redraw(); //infinite redraw
 			}
-			Event::WindowEvent { event, .. } => match event {
-				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-				_ => (),
-			},
-			Event::LoopDestroyed => return,
+			Event::WindowEvent {
+				event: WindowEvent::CloseRequested, ..
+			} => *control_flow = ControlFlow::Exit,
 			_ => (),
 		}
 	});
@@ -71,7 +74,6 @@ where
 				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
 				e => handle_event(&gl_window, e),
 			},
-			Event::LoopDestroyed => return,
 			_ => (),
 		}
 	});
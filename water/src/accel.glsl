@@ -1,11 +1,31 @@
 /*
 	Acceleration function for 2D linear waves + damping.
 
-	Acceleration = -gradient(height) - damping * velocity
+	Acceleration = divergence(c² gradient(height)) - tension * laplacian(laplacian(height)) - damping * velocity
 
-	This is in "natural" units, leading to unit wave speed.
+	The damping coefficient is read from the damping map (main.rs),
+	which includes the sponge layer of absorbing boundaries.
+
+	This is in "natural" units, leading to unit wave speed in the deepest water.
 	Actual wave speed can be controlled by the time step (verlet.glsl).
 
+	The local wave speed squared, c², is the relative water depth (bathymetry, between 0 and 1),
+	as for shallow water waves. Waves slow down and steepen in shallow water (shoaling),
+	and their fronts bend towards shallower regions (refraction).
+	c² is averaged between neighbouring cells, so the flux between them is symmetric.
+
+	Surface pressure (of boats, boats.glsl), in units of height, adds to the height in the flux:
+	the surface is pushed down until height + pressure is level.
+
+	The Laplacian stencil is given by the taps (dx, dy, weight) set from stencil.rs.
+
+	The biharmonic surface tension term makes waves dispersive, like capillary waves:
+	the angular frequency is ω² = 0.25 c² k² + tension k⁴, so short ripples outrun long waves.
+	It is precomputed by applying laplacian.glsl twice.
+
+	Cells outside the grid are handled according to boundary.glsl.
+	Walls (obstacles.glsl) reflect: there is no flux into or out of them.
+
 	https://en.wikipedia.org/wiki/Wave_equation
 */
 #version 450 core
@@ -15,21 +35,61 @@ layout (local_size_x = 16, local_size_y = 16) in;
 layout (binding = 0, r32f) uniform readonly  image2D height;
 layout (binding = 1, r32f) uniform readonly  image2D velocity;
 layout (binding = 2, r32f) uniform writeonly image2D acceleration;
+layout (binding = 3, r32f) uniform readonly  image2D bilaplacian; // of the height, see laplacian.glsl
+layout (binding = 4, r32f) uniform readonly  image2D pressure;
+layout (binding = 5, r32f) uniform readonly  image2D damping;
+layout (binding = 6, r32f) uniform readonly  image2D bathymetry;
+
+uniform int   num_taps;
+uniform vec3  taps[96]; // (dx, dy, weight), see stencil.rs
+uniform float tension = 0.0;
+
+// Whether there is a wall on the line between cell xy and xy + dxy (both excluded).
+// Cells are checked one per step along the longer axis, both cells where the line passes
+// exactly halfway between two, so that the line is blocked the same in both directions.
+bool blocked(ivec2 xy, ivec2 dxy){
+	ivec2 size = imageSize(height);
+	int n = max(abs(dxy.x), abs(dxy.y));
+	for (int i = 1; i < n; i++){
+		vec2 f = vec2(dxy * i) / float(n);
+		if (is_wall(boundary_cell(xy + ivec2(floor(f + 0.5)), size)) || is_wall(boundary_cell(xy + ivec2(ceil(f - 0.5)), size))){
+			return true;
+		}
+	}
+	return false;
+}
 
-uniform float damping;
+// Flux c² (z - z0) from cell xy + dxy into the center cell xy (with height + pressure z0, depth d0).
+// Nothing flows through walls, also not from beyond a wall in between (for the wide deep water stencil).
+float flux(ivec2 xy, ivec2 dxy, float z0, float d0){
+	ivec2 size = imageSize(height);
+	ivec2 c = boundary_cell(xy + dxy, size);
+	// only taps beyond the neighbours have cells in between
+	if (is_wall(c) || (max(abs(dxy.x), abs(dxy.y)) > 1 && blocked(xy, dxy))){
+		return 0.0;
+	}
+	bool inside = all(greaterThanEqual(c, ivec2(0))) && all(lessThan(c, size));
+	float z = imageLoad(height, c).r + imageLoad(pressure, c).r; // zero outside (fixed boundary)
+	float d = inside ? imageLoad(bathymetry, c).r : d0;
+	return 0.5 * (d + d0) * (z - z0);
+}
 
 void main(){
 	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
 
-	float z0 = imageLoad(height, xy + ivec2( 0, 0)).r;
-	float z1 = imageLoad(height, xy + ivec2(-1, 0)).r;
-	float z2 = imageLoad(height, xy + ivec2( 1, 0)).r;
-	float z3 = imageLoad(height, xy + ivec2( 0, 1)).r;
-	float z4 = imageLoad(height, xy + ivec2( 0,-1)).r;
+	float z0 = imageLoad(height, xy).r + imageLoad(pressure, xy).r;
+	float d0 = imageLoad(bathymetry, xy).r;
 	float v  = imageLoad(velocity, xy).r;
 
-	float a = 0.25 * (z1+z2+z3+z4) - z0;
-	a -= damping * v;
+	float a = 0.0;
+	for (int i = 0; i < num_taps; i++){
+		a += taps[i].z * flux(xy, ivec2(taps[i].xy), z0, d0);
+	}
+	a *= 0.25;
+	if (tension != 0.0){
+		a -= tension * imageLoad(bilaplacian, xy).r;
+	}
+	a -= imageLoad(damping, xy).r * v;
 
 	imageStore(acceleration, xy, vec4(a, 0.0, 0.0, 0.0));
-}
\ No newline at end of file
+}
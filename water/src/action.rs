@@ -0,0 +1,72 @@
+use gl_win::KeyMap;
+
+/// Things that can be done with a key press (see keys.txt).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+	Pause,
+	Step,
+	Reset,
+	Faster,
+	Slower,
+	ToggleCaustics,
+	ToggleReflection,
+	ToggleSun,
+	ToggleHud,
+	ToolPush,
+	ToolCalm,
+	ToolWall,
+	ToolDepth,
+	BoatFaster,
+	BoatSlower,
+	BoatLeft,
+	BoatRight,
+	DropBall,
+	SaveSnapshot,
+	LoadSnapshot,
+	Quit,
+}
+
+impl Action {
+	pub fn from_name(name: &str) -> Option<Self> {
+		use Action::*;
+		Some(match name {
+			"pause" => Pause,
+			"step" => Step,
+			"reset" => Reset,
+			"faster" => Faster,
+			"slower" => Slower,
+			"toggle_caustics" => ToggleCaustics,
+			"toggle_reflection" => ToggleReflection,
+			"toggle_sun" => ToggleSun,
+			"toggle_hud" => ToggleHud,
+			"tool_push" => ToolPush,
+			"tool_calm" => ToolCalm,
+			"tool_wall" => ToolWall,
+			"tool_depth" => ToolDepth,
+			"boat_faster" => BoatFaster,
+			"boat_slower" => BoatSlower,
+			"boat_left" => BoatLeft,
+			"boat_right" => BoatRight,
+			"drop_ball" => DropBall,
+			"save_snapshot" => SaveSnapshot,
+			"load_snapshot" => LoadSnapshot,
+			"quit" => Quit,
+			_ => return None,
+		})
+	}
+}
+
+/// The default key bindings (keys.txt),
+/// overridden by bindings from file `fname`, if any.
+pub fn load_keymap(fname: Option<&str>) -> Result<KeyMap, String> {
+	let mut keymap = KeyMap::parse(include_str!("keys.txt")).expect("parse keys.txt");
+	if let Some(fname) = fname {
+		keymap.load(fname)?;
+	}
+	for (key, action) in keymap.bindings() {
+		if Action::from_name(action).is_none() {
+			return Err(format!("key {}: unknown action `{}`", key, action));
+		}
+	}
+	Ok(keymap)
+}
@@ -0,0 +1,58 @@
+/*
+	Semi-Lagrangian advection along the background current (current.rs):
+	each cell takes the value found upstream, where the water came from during the time step,
+	bilinearly interpolated. Unconditionally stable, at the price of slight smoothing.
+
+	Works on fields of one to four components (height, velocity, shallow water flow).
+	Walls (obstacles.glsl) keep their values and are not interpolated from,
+	cells beyond the window edges follow boundary.glsl.
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0)       uniform sampler2D src;
+layout (binding = 0, rg32f) uniform readonly image2D current; // in pixels per unit of time
+layout (binding = 1)       uniform writeonly image2D dst;
+
+uniform float dt;
+
+// Value of cell xy, or none (weight 0) for walls and for cells beyond a fixed boundary.
+vec4 load(ivec2 xy, out float weight){
+	ivec2 size = textureSize(src, 0);
+	ivec2 c = boundary_cell(xy, size);
+	bool inside = all(greaterThanEqual(c, ivec2(0))) && all(lessThan(c, size));
+	weight = inside && !is_wall(c) ? 1.0 : 0.0;
+	return weight > 0.0 ? texelFetch(src, c, 0) : vec4(0.0);
+}
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+	vec4 v0 = texelFetch(src, xy, 0);
+
+	if (is_wall(xy)){
+		imageStore(dst, xy, v0);
+		return;
+	}
+
+	// trace back along the current, midpoint rule
+	vec2 u = imageLoad(current, xy).xy;
+	vec2 mid = vec2(xy) - 0.5 * dt * u;
+	u = imageLoad(current, boundary_cell(ivec2(round(mid)), imageSize(current))).xy;
+	vec2 p = vec2(xy) - dt * u;
+
+	ivec2 c = ivec2(floor(p));
+	vec2 t = fract(p);
+	vec4 sum = vec4(0.0);
+	float total = 0.0;
+	for (int i = 0; i < 4; i++){
+		ivec2 d = ivec2(i & 1, i >> 1);
+		float w;
+		vec4 v = load(c + d, w);
+		w *= (d.x == 1 ? t.x : 1.0 - t.x) * (d.y == 1 ? t.y : 1.0 - t.y);
+		sum += w * v;
+		total += w;
+	}
+
+	imageStore(dst, xy, total > 0.0 ? sum / total : v0);
+}
@@ -0,0 +1,25 @@
+/*
+	Carry the caustics (photon map, see photon.glsl) along the background current (current.rs),
+	over the time of a frame, before udecay.glsl blends them with the new ones.
+	Without, the caustics of the previous frames would smear behind the moving waves.
+
+	Semi-Lagrangian, like advect.glsl, but taking the nearest photon map pixel upstream.
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0)          uniform usampler2D src;
+layout (binding = 0, rg32f)   uniform readonly image2D current; // in pixels per unit of time
+layout (binding = 1, rgba8ui) uniform writeonly uimage2D dst;
+
+uniform float dt; // of the frame
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+	ivec2 size = textureSize(src, 0);
+	vec2 p = vec2(xy) - dt * imageLoad(current, xy).xy;
+	ivec2 c = boundary_cell(ivec2(round(p)), size);
+	bool inside = all(greaterThanEqual(c, ivec2(0))) && all(lessThan(c, size));
+	imageStore(dst, xy, inside ? texelFetch(src, c, 0) : uvec4(0));
+}
@@ -1,33 +1,69 @@
 /*
-	Add a gaussian disturbance around the mouse position.
+	Add a gaussian disturbance around each source:
+	pointers (mouse cursor, touch points) sweeping along their path during a time step,
+	raindrops (rain.rs) and scripted sources (scene.rs).
 
-	Used to trigger waves as the mouse moves over the water surface.
+	Moving sources also push the water along:
+	the shallow water model (drag_flow) drags the flow towards the source velocity,
+	the wave model gets a bow wave, raised ahead of the source and lowered behind it.
 */
 #version 450 core
 
 layout (local_size_x = 16, local_size_y = 16) in;
 
-layout (binding = 0, r32f) uniform image2D dst;
+layout (binding = 0, r32f)    uniform image2D dst;
+layout (binding = 1, rgba32f) uniform image2D flow; // only with drag_flow, see swe_flow.glsl
 
-uniform ivec2 mouse_pos;
-uniform float mouse_pow;
-uniform float mouse_rad;
+// See pointers.rs.
+struct Source {
+	vec2  a;   // segment start, in pixels
+	vec2  b;   // segment end, in pixels (equal to a for a point)
+	float pow; // strength, negative pulls the surface up
+	float rad; // radius in pixels
+	vec2  vel; // velocity, in pixels per unit of time
+};
 
-#define PI 3.1415926535
+layout (std430, binding = 0) readonly buffer Sources {
+	Source sources[];
+};
+
+uniform int  num_sources;
+uniform bool drag_flow = false;
+
+// Point nearest to p on the line segment from a to b.
+vec2 nearest(vec2 p, vec2 a, vec2 b){
+	vec2 ab = b - a;
+	float t = dot(ab, ab) > 0.0 ? clamp(dot(p - a, ab) / dot(ab, ab), 0.0, 1.0) : 0.0;
+	return a + t * ab;
+}
 
 void main(){
 	ivec2 pix = ivec2(gl_GlobalInvocationID.xy);
 
 	float d = imageLoad(dst, pix).r;
+	vec4 f = drag_flow ? imageLoad(flow, pix) : vec4(0.0);
 	vec2 pos = vec2(pix);
-	vec2 mouse = vec2(mouse_pos);
 
-	float mdist = distance(pos, mouse);
-	if (mdist < mouse_rad){
-		float x = mdist / (0.3*mouse_rad);
-		float e = exp(-x*x);
-		d -= e * mouse_pow;
+	for (int i = 0; i < num_sources; i++){
+		Source s = sources[i];
+		vec2 r = pos - nearest(pos, s.a, s.b);
+		float mdist = length(r);
+		if (mdist < s.rad){
+			float x = mdist / (0.3*s.rad);
+			float e = exp(-x*x);
+			d -= e * s.pow;
+			if (length(s.vel) > 0.0){
+				if (drag_flow){
+					f.xy = mix(f.xy, s.vel, 0.5 * e);
+				} else {
+					d += e * s.pow * dot(r, normalize(s.vel)) / (0.3*s.rad);
+				}
+			}
+		}
 	}
 
 	imageStore(dst, pix, vec4(d, 0.0, 0.0, 0.0));
-}
\ No newline at end of file
+	if (drag_flow){
+		imageStore(flow, pix, f);
+	}
+}
@@ -0,0 +1,51 @@
+/*
+	Hull pressure of the boats (boats.rs), in units of surface height:
+	a smooth elliptical footprint under each hull, deepest at its center.
+
+	accel.glsl and swe_flow.glsl add the pressure to the surface height in the pressure gradient,
+	so a boat at rest sits in a depression of depth `draft`.
+
+	Boats wrap around the window edges (boats.rs), hulls crossing an edge press on both sides.
+	Floating bodies (bodies.glsl) add their pressure afterwards.
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, r32f) uniform writeonly image2D pressure;
+
+// See boats.rs.
+struct Hull {
+	vec2  pos;    // center, in pixels
+	vec2  dir;    // heading, unit vector
+	float length; // in pixels
+	float width;  // in pixels
+	float draft;  // pressure at the center
+	float pad;
+};
+
+layout (std430, binding = 2) readonly buffer Hulls {
+	Hull hulls[];
+};
+
+uniform int num_boats;
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+	vec2 size = vec2(imageSize(pressure));
+
+	float p = 0.0;
+	for (int i = 0; i < num_boats; i++){
+		Hull h = hulls[i];
+		vec2 r = vec2(xy) - h.pos;
+		r -= size * round(r / size);
+		float u = dot(r, h.dir) / (0.5 * h.length);
+		float v = dot(r, vec2(-h.dir.y, h.dir.x)) / (0.5 * h.width);
+		float s = 1.0 - u * u - v * v;
+		if (s > 0.0){
+			p += h.draft * s * s;
+		}
+	}
+
+	imageStore(pressure, xy, vec4(p, 0.0, 0.0, 0.0));
+}
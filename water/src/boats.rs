@@ -0,0 +1,213 @@
+/*
+	Boats: moving pressure sources, like hulls pushing the water down.
+
+	A boat either sails a closed path of waypoints (--boat options),
+	or is steered with the keyboard (--steer, see keys.txt).
+
+	Every time step, boats.glsl rasterizes the hulls into a pressure field,
+	which accel.glsl and swe_flow.glsl add to the surface height in the pressure gradient.
+	A boat thus rides in a depression of its own, pushing up a bow wave.
+	In dispersive deep water (--stencil deep), it trails a V-shaped Kelvin wake.
+	Boats moving faster than non-dispersive waves trail a Mach cone instead.
+
+	water.frag draws the hulls.
+
+	https://en.wikipedia.org/wiki/Kelvin_wake_pattern
+*/
+use crate::spec::*;
+use gl_obj::*;
+
+/// Maximum number of boats, more are ignored.
+pub const MAX_BOATS: usize = 16;
+
+/// Speed change of the steered boat per key press, in cells per unit of time.
+const SPEED_STEP: f32 = 0.05;
+
+/// Top speed of the steered boat, in cells per unit of time.
+const MAX_SPEED: f32 = 1.0;
+
+/// Heading change of the steered boat per key press, in degrees.
+const TURN_STEP: f32 = 10.0;
+
+/// A hull, laid out as in boats.glsl and water.frag.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Hull {
+	x: f32,
+	y: f32,
+	dx: f32, // heading, unit vector
+	dy: f32,
+	length: f32,
+	width: f32,
+	draft: f32, // pressure, in units of surface height
+	_pad: f32,
+}
+
+#[derive(Clone, Debug)]
+struct Boat {
+	pos: (f32, f32),
+	heading: f32, // radians, clockwise on screen from the x axis
+	speed: f32,   // cells per unit of time
+	length: f32,
+	width: f32,
+	draft: f32,
+	path: Vec<(f32, f32)>, // waypoints, empty for the steered boat
+	next: usize,           // waypoint sailing to
+}
+
+impl Boat {
+	fn new(pos: (f32, f32)) -> Self {
+		Self {
+			pos,
+			heading: 0.0,
+			speed: 0.4,
+			length: 30.0,
+			width: 10.0,
+			draft: 0.5,
+			path: Vec::new(),
+			next: 0,
+		}
+	}
+
+	/// Sail for time `dt`, on a periodic grid of given size.
+	fn advance(&mut self, dt: f32, size: uvec2) {
+		let step = self.speed * dt;
+		if !self.path.is_empty() {
+			let target = self.path[self.next];
+			let (tx, ty) = (target.0 - self.pos.0, target.1 - self.pos.1);
+			if tx * tx + ty * ty < f32::max(step * step, 0.25 * self.length * self.length) {
+				self.next = (self.next + 1) % self.path.len();
+			}
+			// turn towards the waypoint, on a circle no smaller than the boat.
+			let mut turn = f32::atan2(ty, tx) - self.heading;
+			turn = f32::atan2(turn.sin(), turn.cos());
+			let max_turn = step / self.length;
+			self.heading += turn.clamp(-max_turn, max_turn);
+		}
+		let (w, h) = (size.0 as f32, size.1 as f32);
+		self.pos.0 = (self.pos.0 + step * self.heading.cos()).rem_euclid(w);
+		self.pos.1 = (self.pos.1 + step * self.heading.sin()).rem_euclid(h);
+	}
+
+	fn hull(&self) -> Hull {
+		Hull {
+			x: self.pos.0,
+			y: self.pos.1,
+			dx: self.heading.cos(),
+			dy: self.heading.sin(),
+			length: self.length,
+			width: self.width,
+			draft: self.draft,
+			_pad: 0.0,
+		}
+	}
+}
+
+pub struct Boats {
+	boats: Vec<Boat>,
+	buffer: Buffer,
+	size: uvec2,
+}
+
+impl Boats {
+	/// No boats yet, on a grid of given size.
+	pub fn new(size: uvec2) -> Self {
+		Self {
+			boats: Vec::new(),
+			buffer: Buffer::create().storage(&[Hull::default(); MAX_BOATS], gl::DYNAMIC_STORAGE_BIT),
+			size,
+		}
+	}
+
+	/// Add a boat sailing a closed path, given as waypoints followed by optional parameters:
+	///
+	///     x0 y0 x1 y1 ... speed=0.4 length=30 width=10 draft=0.5
+	///
+	/// Speed is in cells per unit of time, draft in units of surface height.
+	pub fn add_path(&mut self, text: &str) -> Result<(), String> {
+		let spec = Spec::parse(text)?;
+		let coords = spec.words.iter().map(|w| number(w)).collect::<Result<Vec<_>, _>>()?;
+		if coords.len() < 4 || coords.len() % 2 != 0 {
+			return Err(format!("want at least two waypoints (x y pairs), have {} numbers", coords.len()));
+		}
+		let path: Vec<(f32, f32)> = coords.chunks(2).map(|c| (c[0], c[1])).collect();
+
+		let mut boat = Boat::new(path[0]);
+		for &(key, value) in &spec.params {
+			match key {
+				"speed" => boat.speed = value,
+				"length" => boat.length = value,
+				"width" => boat.width = value,
+				"draft" => boat.draft = value,
+				_ => return Err(format!("unknown parameter `{}`, options: speed, length, width, draft", key)),
+			}
+		}
+		boat.heading = f32::atan2(path[1].1 - path[0].1, path[1].0 - path[0].0);
+		boat.path = path;
+		boat.next = 1;
+		self.push(boat)
+	}
+
+	/// Add a boat steered with the keyboard, at rest in the middle of the grid.
+	pub fn add_steered(&mut self) -> Result<(), String> {
+		let mut boat = Boat::new(((self.size.0 / 2) as f32, (self.size.1 / 2) as f32));
+		boat.speed = 0.0;
+		self.push(boat)
+	}
+
+	fn push(&mut self, boat: Boat) -> Result<(), String> {
+		if self.boats.len() == MAX_BOATS {
+			return Err(format!("more than {} boats", MAX_BOATS));
+		}
+		self.boats.push(boat);
+		Ok(())
+	}
+
+	/// The steered boat, if any.
+	fn steered(&mut self) -> Option<&mut Boat> {
+		self.boats.iter_mut().find(|b| b.path.is_empty())
+	}
+
+	/// Speed up (1) or slow down (-1) the steered boat.
+	pub fn throttle(&mut self, dir: f32) {
+		if let Some(b) = self.steered() {
+			b.speed = (b.speed + dir * SPEED_STEP).clamp(0.0, MAX_SPEED);
+		}
+	}
+
+	/// Turn the steered boat right (1) or left (-1).
+	pub fn turn(&mut self, dir: f32) {
+		if let Some(b) = self.steered() {
+			b.heading += dir * TURN_STEP.to_radians();
+		}
+	}
+
+	/// Speed of the steered boat, if any.
+	pub fn steered_speed(&self) -> Option<f32> {
+		self.boats.iter().find(|b| b.path.is_empty()).map(|b| b.speed)
+	}
+
+	pub fn len(&self) -> usize {
+		self.boats.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.boats.is_empty()
+	}
+
+	/// Sail all boats for time `dt` and upload their hulls.
+	pub fn advance(&mut self, dt: f32) {
+		for b in &mut self.boats {
+			b.advance(dt, self.size);
+		}
+		let hulls: Vec<Hull> = self.boats.iter().map(Boat::hull).collect();
+		if !hulls.is_empty() {
+			self.buffer.sub_data(0, &hulls);
+		}
+	}
+
+	/// Bind the hull buffer to shader storage block `index`.
+	pub fn bind(&self, index: u32) {
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, index)
+	}
+}
@@ -0,0 +1,121 @@
+/*
+	Floating bodies (bodies.rs), step 1: how deep each body sits in the water.
+
+	In each water cell under a body, the submerged depth is the water surface height
+	above the body's bottom, at most the body's thickness there. The body presses down
+	on the water with a pressure of `coupling` times that depth, added to the pressure field
+	(written by boats.glsl), so a body sinking in pushes the water out from under it.
+
+	The submerged depths are reduced in shared memory to sums per body and work group,
+	along with their moment about the body's center, the wet area, and the surface slope
+	and water velocity (flow plus current) weighted by depth. bodies_reduce.glsl adds up the work groups.
+
+	Walls (obstacles.glsl) are dry, bodies there carry no water.
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, r32f) uniform readonly image2D height;   // water surface
+layout (binding = 1, r32f) uniform          image2D pressure; // bodies' pressure added here
+layout (binding = 2, rg32f) uniform readonly image2D current; // see current.rs
+layout (binding = 0)       uniform sampler2D flow;            // see swe_flow.glsl
+
+// See bodies.rs.
+struct Body {
+	vec2  pos;    // center, in pixels
+	vec2  dir;    // heading, unit vector
+	vec2  tilt;   // slope of the bottom
+	vec2  size;   // radius or half length and width, in pixels
+	float z;      // center height
+	float height; // thickness at the center
+	float shape;  // 0: sphere, 1: box
+	float pad;
+};
+
+layout (std430, binding = 4) readonly buffer Bodies {
+	Body bodies[];
+};
+
+// Sums per work group and body, see bodies_reduce.glsl.
+layout (std430, binding = 5) writeonly buffer Partials {
+	vec4 partials[]; // (volume, moment, wet area), (slope, flow)
+};
+
+#define MAX_BODIES 16
+
+uniform int   num_bodies;
+uniform float coupling = 0.3;
+uniform bool  use_flow = false;
+
+shared vec4 sum_a[gl_WorkGroupSize.x * gl_WorkGroupSize.y];
+shared vec4 sum_b[gl_WorkGroupSize.x * gl_WorkGroupSize.y];
+
+float load_height(ivec2 xy, float h0){
+	ivec2 c = boundary_cell(xy, imageSize(height));
+	return is_wall(c) ? h0 : imageLoad(height, c).r;
+}
+
+// Submerged depth of body b in a cell at offset r from its center, with water surface h.
+float submerged(Body b, vec2 r, float h){
+	float bottom, top;
+	if (b.shape < 0.5){
+		float q = dot(r, r) / (b.size.x * b.size.x);
+		if (q >= 1.0){
+			return 0.0;
+		}
+		float half_height = 0.5 * b.height * sqrt(1.0 - q);
+		bottom = b.z - half_height;
+		top = b.z + half_height;
+	} else {
+		vec2 uv = vec2(dot(r, b.dir), dot(r, vec2(-b.dir.y, b.dir.x)));
+		if (any(greaterThan(abs(uv), b.size))){
+			return 0.0;
+		}
+		bottom = b.z - 0.5 * b.height + dot(b.tilt, r);
+		top = bottom + b.height;
+	}
+	return clamp(h - bottom, 0.0, top - bottom);
+}
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+	ivec2 size = imageSize(height);
+	uint i = gl_LocalInvocationIndex;
+	uint group = gl_WorkGroupID.y * gl_NumWorkGroups.x + gl_WorkGroupID.x;
+
+	bool water = !is_wall(xy);
+	float h = imageLoad(height, xy).r;
+	vec2 slope = 0.5 * vec2(load_height(xy + ivec2(1, 0), h) - load_height(xy - ivec2(1, 0), h),
+	                        load_height(xy + ivec2(0, 1), h) - load_height(xy - ivec2(0, 1), h));
+	vec2 u = (use_flow ? texture(flow, (vec2(xy) + 0.5) / vec2(size)).xy : vec2(0.0)) + imageLoad(current, xy).xy;
+	float p = imageLoad(pressure, xy).r;
+
+	for (int k = 0; k < num_bodies; k++){
+		Body b = bodies[k];
+		vec2 r = vec2(xy) - b.pos;
+		if (boundary == BOUNDARY_PERIODIC){
+			r -= vec2(size) * round(r / vec2(size));
+		}
+		float s = water ? submerged(b, r, h) : 0.0;
+		p += coupling * s;
+
+		sum_a[i] = vec4(s, s * r, s > 0.0 ? 1.0 : 0.0);
+		sum_b[i] = vec4(s * slope, s * u);
+		barrier();
+		for (uint n = gl_WorkGroupSize.x * gl_WorkGroupSize.y / 2; n > 0; n /= 2){
+			if (i < n){
+				sum_a[i] += sum_a[i + n];
+				sum_b[i] += sum_b[i + n];
+			}
+			barrier();
+		}
+		if (i == 0){
+			partials[2 * (group * MAX_BODIES + k)] = sum_a[0];
+			partials[2 * (group * MAX_BODIES + k) + 1] = sum_b[0];
+		}
+		barrier();
+	}
+
+	imageStore(pressure, xy, vec4(p, 0.0, 0.0, 0.0));
+}
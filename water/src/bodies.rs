@@ -0,0 +1,383 @@
+/*
+	Floating bodies: balls and boxes bobbing on the water, two-way coupled.
+
+	Every time step, bodies.glsl measures how deep each body sits in the water,
+	cell by cell under its footprint, and reduces this on the GPU (with bodies_reduce.glsl)
+	to the submerged volume, its moment, the slope and flow under it.
+	The body pushes back with a pressure proportional to its submerged depth,
+	added to the pressure field of accel.glsl and swe_flow.glsl like a boat's hull.
+
+	The bodies are integrated on the CPU: buoyancy lifts them by the submerged volume,
+	the surface slope pushes them sideways, and drag brings them to the speed of the flow
+	and the background current (current.rs).
+	Boxes also rock: the moment of the buoyancy tilts them.
+	A dropped ball sinks in, pushes out a ring of waves and bobs back up,
+	then rocks on the waves of others.
+
+	Units: horizontally pixels, vertically units of surface height,
+	gravity as in swe_flow.glsl. Volumes in pixels times height units.
+*/
+use crate::boundary::Boundary;
+use crate::shaders::*;
+use crate::spec::*;
+use gl::*;
+use gl_obj::*;
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// Maximum number of bodies, dropping more replaces the oldest.
+pub const MAX_BODIES: usize = 16;
+
+/// Pressure of a body, relative to its submerged depth. Stiffer coupling floats bodies closer to
+/// their Archimedes water line, but effectively speeds up waves under the body beyond the stable time step.
+const COUPLING: f32 = 0.3;
+
+/// Drag of the bodies' vertical and rocking motion, per unit of time, when fully afloat.
+const HEAVE_DRAG: f32 = 0.02;
+
+/// Drag towards the flow (for --model swe) and current, per unit of time, when fully afloat.
+const FLOW_DRAG: f32 = 0.02;
+
+/// Work group size of bodies_reduce.glsl.
+const GROUP_SIZE: u32 = 256;
+
+/// Body shapes, numbered as in bodies.glsl.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shape {
+	/// An ellipsoid: round with the given radius, flattened to the given height.
+	Sphere = 0,
+	/// A flat-bottomed box.
+	Box = 1,
+}
+
+impl FromStr for Shape {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"sphere" => Ok(Shape::Sphere),
+			"box" => Ok(Shape::Box),
+			_ => Err(format!("unknown shape `{}`, options: sphere, box", s)),
+		}
+	}
+}
+
+impl fmt::Display for Shape {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			Shape::Sphere => "sphere",
+			Shape::Box => "box",
+		};
+		f.write_str(s)
+	}
+}
+
+/// A body, laid out as in bodies.glsl and water.frag.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+struct GpuBody {
+	x: f32,
+	y: f32,
+	dx: f32, // heading, unit vector
+	dy: f32,
+	sx: f32, // tilt, as the slope of the bottom (height units per pixel)
+	sy: f32,
+	a: f32, // radius, or half length, in pixels
+	b: f32, // radius, or half width, in pixels
+	z: f32, // center height
+	height: f32,
+	shape: f32,
+	_pad: f32,
+}
+
+/// Reduced over a body's footprint, laid out as in bodies_reduce.glsl.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+struct Totals {
+	volume: f32,      // submerged
+	moment: [f32; 2], // of the submerged volume, about the center
+	wet_area: f32,
+	slope: [f32; 2], // volume weighted surface slope
+	flow: [f32; 2],  // volume weighted flow velocity
+}
+
+#[derive(Clone, Debug)]
+struct Body {
+	shape: Shape,
+	pos: (f32, f32, f32),
+	vel: (f32, f32, f32),
+	heading: f32, // radians
+	tilt: (f32, f32),
+	tilt_vel: (f32, f32),
+	a: f32,
+	b: f32,
+	height: f32,
+	density: f32, // relative to water
+}
+
+impl Body {
+	fn volume(&self) -> f32 {
+		match self.shape {
+			Shape::Sphere => 2.0 / 3.0 * PI * self.a * self.b * self.height,
+			Shape::Box => 4.0 * self.a * self.b * self.height,
+		}
+	}
+
+	fn area(&self) -> f32 {
+		match self.shape {
+			Shape::Sphere => PI * self.a * self.b,
+			Shape::Box => 4.0 * self.a * self.b,
+		}
+	}
+
+	fn dir(&self) -> (f32, f32) {
+		(self.heading.cos(), self.heading.sin())
+	}
+
+	/// Advance by time `dt` under the forces from `t`.
+	fn advance(&mut self, t: &Totals, dt: f32, gravity: f32) {
+		let mass = self.density * self.volume();
+		let wet = t.wet_area / self.area();
+
+		let az = gravity * (t.volume - mass) / mass - HEAVE_DRAG * wet * self.vel.2;
+		let ax = (-gravity * t.slope[0] + FLOW_DRAG * (t.flow[0] - t.volume * self.vel.0)) / mass;
+		let ay = (-gravity * t.slope[1] + FLOW_DRAG * (t.flow[1] - t.volume * self.vel.1)) / mass;
+		self.vel.0 += dt * ax;
+		self.vel.1 += dt * ay;
+		self.vel.2 += dt * az;
+		self.pos.0 += dt * self.vel.0;
+		self.pos.1 += dt * self.vel.1;
+		self.pos.2 += dt * self.vel.2;
+
+		// sitting on dry land
+		if self.pos.2 < -self.height {
+			self.pos.2 = -self.height;
+			self.vel.2 = 0.0;
+		}
+
+		// rocking: the buoyancy moment tilts the bottom towards the deeper side,
+		// in the frame of the box, where the moments of inertia are diagonal.
+		if self.shape == Shape::Box {
+			let (dx, dy) = self.dir();
+			let torque = (gravity * (t.moment[0] * dx + t.moment[1] * dy), gravity * (-t.moment[0] * dy + t.moment[1] * dx));
+			let inertia = (mass * self.a * self.a / 3.0, mass * self.b * self.b / 3.0);
+			let (mut su, mut sv) = (self.tilt.0 * dx + self.tilt.1 * dy, -self.tilt.0 * dy + self.tilt.1 * dx);
+			let (mut wu, mut wv) = (self.tilt_vel.0 * dx + self.tilt_vel.1 * dy, -self.tilt_vel.0 * dy + self.tilt_vel.1 * dx);
+			wu += dt * (torque.0 / inertia.0 - HEAVE_DRAG * wet * wu);
+			wv += dt * (torque.1 / inertia.1 - HEAVE_DRAG * wet * wv);
+			su += dt * wu;
+			sv += dt * wv;
+			self.tilt = (su * dx - sv * dy, su * dy + sv * dx);
+			self.tilt_vel = (wu * dx - wv * dy, wu * dy + wv * dx);
+		}
+	}
+
+	/// Keep the body on a grid of given size: wrap around a periodic boundary, bounce off the others.
+	fn keep_inside(&mut self, size: uvec2, boundary: Boundary) {
+		let (w, h) = (size.0 as f32, size.1 as f32);
+		if boundary == Boundary::Periodic {
+			self.pos.0 = self.pos.0.rem_euclid(w);
+			self.pos.1 = self.pos.1.rem_euclid(h);
+			return;
+		}
+		if (self.pos.0 < 0.0 && self.vel.0 < 0.0) || (self.pos.0 > w && self.vel.0 > 0.0) {
+			self.vel.0 = -self.vel.0;
+		}
+		if (self.pos.1 < 0.0 && self.vel.1 < 0.0) || (self.pos.1 > h && self.vel.1 > 0.0) {
+			self.vel.1 = -self.vel.1;
+		}
+	}
+
+	fn gpu(&self) -> GpuBody {
+		let (dx, dy) = self.dir();
+		GpuBody {
+			x: self.pos.0,
+			y: self.pos.1,
+			dx,
+			dy,
+			sx: self.tilt.0,
+			sy: self.tilt.1,
+			a: self.a,
+			b: self.b,
+			z: self.pos.2,
+			height: self.height,
+			shape: self.shape as i32 as f32,
+			_pad: 0.0,
+		}
+	}
+}
+
+pub struct Bodies {
+	p_forces: Program,
+	p_reduce: Program,
+	buffer: Buffer,
+	partials: Buffer,
+	totals: Buffer,
+	bodies: Vec<Body>,
+	size: uvec2,
+	boundary: Boundary,
+	dt: f32,
+	gravity: f32,
+}
+
+impl Bodies {
+	/// No bodies yet, on a grid of given size and boundary.
+	pub fn new(size: uvec2, boundary: Boundary) -> Result<Self, String> {
+		let groups = ((size.0 / 16) * (size.1 / 16)) as usize; // of bodies.glsl
+		let p_forces = link(&[shader!(COMPUTE_SHADER, "bodies.glsl", "boundary.glsl", "obstacles.glsl")?])?;
+		p_forces.set1i("boundary", boundary as i32).set1f("coupling", COUPLING);
+		let p_reduce = link(&[shader!(COMPUTE_SHADER, "bodies_reduce.glsl")?])?;
+		p_reduce.set1i("num_groups", groups as i32);
+		Ok(Self {
+			p_forces,
+			p_reduce,
+			buffer: Buffer::create().storage(&[GpuBody::default(); MAX_BODIES], gl::DYNAMIC_STORAGE_BIT),
+			partials: Buffer::create().storage(&vec![Totals::default(); groups * MAX_BODIES], 0),
+			totals: Buffer::create().storage(&[Totals::default(); MAX_BODIES], gl::DYNAMIC_STORAGE_BIT),
+			bodies: Vec::new(),
+			size,
+			boundary,
+			dt: 1.0,
+			gravity: 1.0,
+		})
+	}
+
+	/// Set the time step, the gravity (as in swe_flow.glsl)
+	/// and whether there is a flow to drag the bodies along (the shallow water model).
+	pub fn set_motion(&mut self, dt: f32, gravity: f32, use_flow: bool) {
+		self.dt = dt;
+		self.gravity = gravity;
+		self.p_forces.set1i("use_flow", use_flow as i32);
+	}
+
+	/// Add a body described by its shape and position, followed by optional parameters:
+	///
+	///     sphere x y radius=12 height=4 density=0.5 drop=10
+	///     box x y length=40 width=16 height=3 density=0.4 angle=0 drop=10
+	///
+	/// Sizes are in pixels, except for the height, which is in units of surface height
+	/// like the drop height, the height above the water the body is dropped from.
+	pub fn add(&mut self, text: &str) -> Result<(), String> {
+		let spec = Spec::parse(text)?;
+		if spec.words.len() < 3 {
+			return Err("want a shape and a position: sphere|box x y ...".into());
+		}
+		if let Some(word) = spec.words.get(3) {
+			return Err(format!("`{}`: want key=value", word));
+		}
+		let shape: Shape = spec.words[0].parse()?;
+		let (x, y) = (number(spec.words[1])?, number(spec.words[2])?);
+
+		let mut body = match shape {
+			Shape::Sphere => Self::sphere(x, y),
+			Shape::Box => Body {
+				shape,
+				a: 20.0,
+				b: 8.0,
+				height: 3.0,
+				density: 0.4,
+				..Self::sphere(x, y)
+			},
+		};
+		let mut drop = 10.0;
+		for &(key, value) in &spec.params {
+			match (shape, key) {
+				(Shape::Sphere, "radius") => {
+					body.a = value;
+					body.b = value;
+				}
+				(Shape::Box, "length") => body.a = 0.5 * value,
+				(Shape::Box, "width") => body.b = 0.5 * value,
+				(Shape::Box, "angle") => body.heading = value.to_radians(),
+				(_, "height") => body.height = value,
+				(_, "density") => body.density = value,
+				(_, "drop") => drop = value,
+				_ => return Err(format!("unknown {} parameter `{}`", shape, key)),
+			}
+		}
+		if !(body.density > 0.0 && body.a > 0.0 && body.b > 0.0 && body.height > 0.0) {
+			return Err("sizes and density must be positive".into());
+		}
+		body.pos.2 = drop + 0.5 * body.height;
+		self.push(body);
+		Ok(())
+	}
+
+	/// Drop a ball at pixel position (x, y).
+	pub fn drop_ball(&mut self, x: f32, y: f32) {
+		let mut ball = Self::sphere(x, y);
+		ball.pos.2 = 10.0 + 0.5 * ball.height;
+		self.push(ball)
+	}
+
+	fn sphere(x: f32, y: f32) -> Body {
+		Body {
+			shape: Shape::Sphere,
+			pos: (x, y, 0.0),
+			vel: (0.0, 0.0, 0.0),
+			heading: 0.0,
+			tilt: (0.0, 0.0),
+			tilt_vel: (0.0, 0.0),
+			a: 12.0,
+			b: 12.0,
+			height: 4.0,
+			density: 0.5,
+		}
+	}
+
+	fn push(&mut self, body: Body) {
+		if self.bodies.len() == MAX_BODIES {
+			self.bodies.remove(0);
+		}
+		self.bodies.push(body);
+		self.upload();
+	}
+
+	pub fn len(&self) -> usize {
+		self.bodies.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bodies.is_empty()
+	}
+
+	/// One time step: add the bodies' pressure to `pressure`,
+	/// then move them by the forces of the water `surface`, `flow` and background `current`.
+	pub fn advance(&mut self, surface: Texture, pressure: Texture, flow: Texture, current: Texture, obstacles: Texture) {
+		if self.bodies.is_empty() {
+			return;
+		}
+		let n = self.bodies.len();
+		self.p_forces.set1i("num_bodies", n as i32);
+		self.bind(4);
+		self.partials.bind_base(gl::SHADER_STORAGE_BUFFER, 5);
+		surface.bind_image_unit(0, READ_ONLY);
+		pressure.bind_image_unit(1, READ_WRITE);
+		current.bind_image_unit(2, READ_ONLY);
+		flow.bind_texture_unit(0);
+		obstacles.bind_image_unit(crate::OBSTACLES_UNIT, READ_ONLY);
+		self.p_forces.compute_and_sync(uvec3(self.size.0, self.size.1, 1));
+
+		self.totals.bind_base(gl::SHADER_STORAGE_BUFFER, 6);
+		self.p_reduce.compute_and_sync(uvec3(n as u32 * GROUP_SIZE, 1, 1));
+		let mut totals = [Totals::default(); MAX_BODIES];
+		self.totals.get_sub_data(0, &mut totals[..n]);
+
+		for (body, t) in self.bodies.iter_mut().zip(&totals) {
+			body.advance(t, self.dt, self.gravity);
+			body.keep_inside(self.size, self.boundary);
+		}
+		self.upload();
+	}
+
+	fn upload(&self) {
+		let bodies: Vec<GpuBody> = self.bodies.iter().map(Body::gpu).collect();
+		self.buffer.sub_data(0, &bodies);
+	}
+
+	/// Bind the body buffer to shader storage block `index`.
+	pub fn bind(&self, index: u32) {
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, index)
+	}
+}
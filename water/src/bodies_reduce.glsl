@@ -0,0 +1,49 @@
+/*
+	Floating bodies (bodies.rs), step 2: add up the sums of the work groups of bodies.glsl.
+	One work group per body.
+*/
+#version 450 core
+
+layout (local_size_x = 256) in;
+
+layout (std430, binding = 5) readonly buffer Partials {
+	vec4 partials[];
+};
+
+// See bodies.rs.
+layout (std430, binding = 6) writeonly buffer Totals {
+	vec4 totals[]; // (volume, moment, wet area), (slope, flow)
+};
+
+#define MAX_BODIES 16
+
+uniform int num_groups; // of bodies.glsl
+
+shared vec4 sum_a[gl_WorkGroupSize.x];
+shared vec4 sum_b[gl_WorkGroupSize.x];
+
+void main(){
+	uint i = gl_LocalInvocationIndex;
+	uint k = gl_WorkGroupID.x;
+
+	vec4 a = vec4(0.0);
+	vec4 b = vec4(0.0);
+	for (uint g = i; g < num_groups; g += gl_WorkGroupSize.x){
+		a += partials[2 * (g * MAX_BODIES + k)];
+		b += partials[2 * (g * MAX_BODIES + k) + 1];
+	}
+	sum_a[i] = a;
+	sum_b[i] = b;
+	barrier();
+	for (uint n = gl_WorkGroupSize.x / 2; n > 0; n /= 2){
+		if (i < n){
+			sum_a[i] += sum_a[i + n];
+			sum_b[i] += sum_b[i + n];
+		}
+		barrier();
+	}
+	if (i == 0){
+		totals[2 * k] = sum_a[0];
+		totals[2 * k + 1] = sum_b[0];
+	}
+}
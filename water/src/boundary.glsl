@@ -0,0 +1,34 @@
+/*
+	Boundary conditions at the edges of the simulation grid.
+	Included by shaders that read neighbouring cells (see boundary.rs).
+
+	  * fixed:      cells outside the grid are held at zero (Dirichlet).
+	  * reflective: cells outside mirror the cells inside (Neumann, zero gradient).
+	  * periodic:   the grid wraps around.
+	  * absorbing:  reflective, plus a damping sponge layer along the edges
+	                (added to the damping map, see boundary.rs).
+*/
+
+#define BOUNDARY_FIXED      0
+#define BOUNDARY_REFLECTIVE 1
+#define BOUNDARY_PERIODIC   2
+#define BOUNDARY_ABSORBING  3
+
+uniform int boundary = BOUNDARY_FIXED;
+
+// The cell to read instead of `xy`, which may lie outside a grid of given size.
+// For a fixed boundary, the cell is returned unchanged:
+// imageLoad outside the image returns zero.
+ivec2 boundary_cell(ivec2 xy, ivec2 size) {
+	switch (boundary) {
+	case BOUNDARY_PERIODIC:
+		return (xy % size + size) % size;
+	case BOUNDARY_REFLECTIVE:
+	case BOUNDARY_ABSORBING:
+		xy = mix(xy, -xy - 1, lessThan(xy, ivec2(0)));
+		xy = mix(xy, 2 * size - xy - 1, greaterThanEqual(xy, size));
+		return xy;
+	default:
+		return xy;
+	}
+}
@@ -0,0 +1,97 @@
+use gl_obj::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// Damping at the very edge of an absorbing boundary.
+const SPONGE_DAMPING: f32 = 0.3;
+
+/// Boundary conditions at the edges of the simulation grid,
+/// numbered as in boundary.glsl.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Boundary {
+	Fixed = 0,
+	Reflective = 1,
+	Periodic = 2,
+	Absorbing = 3,
+}
+
+impl Boundary {
+	/// Set the texture wrap mode matching the boundary,
+	/// for textures sampled beyond the grid edges.
+	pub fn wrap(self, tex: Texture) -> Texture {
+		match self {
+			Boundary::Periodic => tex.repeat(),
+			Boundary::Reflective => tex.mirrored_repeat(),
+			Boundary::Fixed | Boundary::Absorbing => tex.clamp_to_edge(),
+		}
+	}
+
+	/// Extra damping at cell (x, y) of a grid of given size, due to the absorbing layer:
+	/// rises quadratically from 0 at `width` cells from the edge to SPONGE_DAMPING at the edge.
+	/// Only the edge cells are damped for a width of zero. Zero for other boundaries.
+	pub fn sponge(self, x: u32, y: u32, size: uvec2, width: f32) -> f32 {
+		if self != Boundary::Absorbing {
+			return 0.0;
+		}
+		let d = x.min(y).min(size.0 - 1 - x).min(size.1 - 1 - y) as f32;
+		if width <= 0.0 {
+			return if d == 0.0 { SPONGE_DAMPING } else { 0.0 };
+		}
+		let s = ((width - d) / width).clamp(0.0, 1.0);
+		SPONGE_DAMPING * s * s
+	}
+}
+
+impl FromStr for Boundary {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"fixed" => Ok(Boundary::Fixed),
+			"reflective" => Ok(Boundary::Reflective),
+			"periodic" => Ok(Boundary::Periodic),
+			"absorbing" => Ok(Boundary::Absorbing),
+			_ => Err(format!("unknown boundary `{}`, options: fixed, reflective, periodic, absorbing", s)),
+		}
+	}
+}
+
+impl fmt::Display for Boundary {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			Boundary::Fixed => "fixed",
+			Boundary::Reflective => "reflective",
+			Boundary::Periodic => "periodic",
+			Boundary::Absorbing => "absorbing",
+		};
+		f.write_str(s)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sponge_profile() {
+		let size = uvec2(200, 100);
+		let sponge = |x, y, width| Boundary::Absorbing.sponge(x, y, size, width);
+		assert_eq!(sponge(0, 50, 32.0), SPONGE_DAMPING);
+		assert_eq!(sponge(199, 50, 32.0), SPONGE_DAMPING);
+		assert_eq!(sponge(16, 50, 32.0), 0.25 * SPONGE_DAMPING);
+		assert_eq!(sponge(100, 50, 32.0), 0.0);
+		assert_eq!(Boundary::Fixed.sponge(0, 0, size, 32.0), 0.0);
+	}
+
+	#[test]
+	fn sponge_zero_width() {
+		let size = uvec2(100, 50);
+		for &width in &[0.0, -4.0] {
+			let sponge = |x, y| Boundary::Absorbing.sponge(x, y, size, width);
+			assert_eq!(sponge(0, 25), SPONGE_DAMPING, "width {}", width);
+			assert_eq!(sponge(50, 49), SPONGE_DAMPING, "width {}", width);
+			assert_eq!(sponge(1, 25), 0.0, "width {}", width);
+			assert_eq!(sponge(50, 25), 0.0, "width {}", width);
+		}
+	}
+}
@@ -0,0 +1,24 @@
+/*
+	Clamp the water height to [-max_height, max_height],
+	bringing clamped cells to rest.
+
+	Used to recover from runaway amplitudes (see stability.rs).
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, r32f) uniform image2D pos;
+layout (binding = 1, r32f) uniform image2D vel;
+
+uniform float max_height;
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+
+	float p = imageLoad(pos, xy).r;
+	if (abs(p) > max_height){
+		imageStore(pos, xy, vec4(clamp(p, -max_height, max_height), 0.0, 0.0, 0.0));
+		imageStore(vel, xy, vec4(0.0));
+	}
+}
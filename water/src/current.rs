@@ -0,0 +1,92 @@
+/*
+	Background currents: a steady flow carrying the waves along, for rivers and whirlpools.
+
+	Every time step, advect.glsl moves the height and velocity fields along the current
+	(semi-Lagrangian: each cell takes the value found upstream, one time step back),
+	and every frame it carries the caustics along over the frame's time.
+	Floating particles drift with the current too.
+
+	The current is a fixed velocity field, it is not changed by the waves.
+*/
+use gl_img::*;
+use gl_obj::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// Radius of the vortex core (turning like a solid body), relative to the smaller grid side.
+const VORTEX_CORE: f32 = 0.15;
+
+/// Background currents.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Current {
+	/// Still water.
+	None,
+	/// The same velocity everywhere.
+	Uniform,
+	/// A whirlpool in the middle of the grid, turning counterclockwise on screen for positive speeds:
+	/// a solid body core, the speed falling off with the distance beyond (Rankine vortex).
+	Vortex,
+	/// Velocities from an image: red and green are the x and y components,
+	/// from -speed (0) through still (0.5) to +speed (1).
+	Map,
+}
+
+impl Current {
+	/// Current velocity in every cell, row by row, in pixels per unit of time,
+	/// given the (largest) speed, the direction (degrees from the x axis) for a uniform current,
+	/// and the image file for a map.
+	pub fn velocities(self, size: uvec2, speed: f32, dir: f32, map: Option<&str>) -> Result<Vec<[f32; 2]>, String> {
+		let (w, h) = (size.0 as usize, size.1 as usize);
+		Ok(match self {
+			Current::None => vec![[0.0, 0.0]; w * h],
+			Current::Uniform => vec![[speed * dir.to_radians().cos(), speed * dir.to_radians().sin()]; w * h],
+			Current::Vortex => {
+				let core = VORTEX_CORE * f32::min(w as f32, h as f32);
+				let mut v = Vec::with_capacity(w * h);
+				for y in 0..h {
+					for x in 0..w {
+						let (dx, dy) = (x as f32 - 0.5 * w as f32, y as f32 - 0.5 * h as f32);
+						let r = f32::max(f32::sqrt(dx * dx + dy * dy), 1e-3);
+						let s = if r < core { speed * r / core } else { speed * core / r };
+						// screen y points down: counterclockwise on screen
+						v.push([s * dy / r, -s * dx / r]);
+					}
+				}
+				v
+			}
+			Current::Map => {
+				let fname = map.ok_or("--current map needs a --current-map image")?;
+				load_rgb_image(fname, size)?
+					.into_iter()
+					.map(|c| [speed * (2.0 * c[0] - 1.0), speed * (2.0 * c[1] - 1.0)])
+					.collect()
+			}
+		})
+	}
+}
+
+impl FromStr for Current {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"none" => Ok(Current::None),
+			"uniform" => Ok(Current::Uniform),
+			"vortex" => Ok(Current::Vortex),
+			"map" => Ok(Current::Map),
+			_ => Err(format!("unknown current `{}`, options: none, uniform, vortex, map", s)),
+		}
+	}
+}
+
+impl fmt::Display for Current {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			Current::None => "none",
+			Current::Uniform => "uniform",
+			Current::Vortex => "vortex",
+			Current::Map => "map",
+		};
+		f.write_str(s)
+	}
+}
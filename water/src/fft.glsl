@@ -0,0 +1,48 @@
+/*
+	One pass of a radix-2 Stockham FFT along the rows (axis 0) or columns (axis 1) of a complex image.
+	The transform is inverse and unnormalized:
+
+		out[n] = Σ in[k] exp(2πi nk / N)
+
+	log2(N) passes, with span = 1, 2, 4, ... N/2, ping-ponging between two images,
+	transform a whole axis (ocean.rs). Unlike Cooley-Tukey, Stockham needs no bit reversal:
+	results come out in natural order. The size N along the axis must be a power of two.
+
+	https://en.wikipedia.org/wiki/Fast_Fourier_transform
+*/
+#version 450 core
+
+#define PI 3.14159265358979
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, rg32f) uniform readonly  image2D src;
+layout (binding = 1, rg32f) uniform writeonly image2D dst;
+
+uniform int axis;
+uniform int span;
+
+vec2 cmul(vec2 a, vec2 b){
+	return vec2(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+
+	int n = imageSize(src)[axis];
+	int o = xy[axis];
+	int k = o % span;                         // index in the sub-transform
+	int j = (o / (2 * span)) * span + k;      // input pair j, j + n/2
+	bool upper = o % (2 * span) >= span;      // output o is the difference of the butterfly
+
+	ivec2 i0 = xy;
+	ivec2 i1 = xy;
+	i0[axis] = j;
+	i1[axis] = j + n / 2;
+
+	float angle = PI * float(k) / float(span);
+	vec2 a = imageLoad(src, i0).xy;
+	vec2 b = cmul(imageLoad(src, i1).xy, vec2(cos(angle), sin(angle)));
+
+	imageStore(dst, xy, vec4(upper ? a - b : a + b, 0.0, 0.0));
+}
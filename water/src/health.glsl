@@ -0,0 +1,42 @@
+/*
+	Health check of the height field, to catch numerical blow-up (see stability.rs).
+
+	Reduces the heights to their largest magnitude and the number of NaN/Inf cells:
+	first within each work group in shared memory, then into the Health buffer,
+	which must be zeroed beforehand.
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, r32f) uniform readonly image2D height;
+
+// See stability.rs.
+layout (std430, binding = 1) buffer Health {
+	uint max_height; // float bits: for non-negative floats, the bit patterns order like the values
+	uint non_finite;
+};
+
+shared uint group_max;
+shared uint group_non_finite;
+
+void main(){
+	if (gl_LocalInvocationIndex == 0){
+		group_max = 0;
+		group_non_finite = 0;
+	}
+	barrier();
+
+	float z = imageLoad(height, ivec2(gl_GlobalInvocationID.xy)).r;
+	if (isnan(z) || isinf(z)){
+		atomicAdd(group_non_finite, 1);
+	} else {
+		atomicMax(group_max, floatBitsToUint(abs(z)));
+	}
+	barrier();
+
+	if (gl_LocalInvocationIndex == 0){
+		atomicMax(max_height, group_max);
+		atomicAdd(non_finite, group_non_finite);
+	}
+}
@@ -0,0 +1,27 @@
+/*
+	Heads-up display fragment shader.
+
+	Overlays a text bitmap (see hud.rs) in the top-left corner,
+	white text on a translucent dark background.
+	Fragments outside the text area are discarded.
+*/
+#version 450 core
+
+in  vec2 frag_tex_coord;
+out vec4 output_color;
+
+layout(binding = 0) uniform sampler2D text; // 1 texel per font pixel
+
+uniform ivec2 viewport;      // window size in pixels
+uniform int   scale  = 2;    // window pixels per font pixel
+uniform int   margin = 4;    // distance to window edge, in font pixels
+
+void main() {
+	ivec2 pix  = ivec2(frag_tex_coord * viewport) / scale - ivec2(margin);
+	ivec2 size = textureSize(text, 0);
+	if (any(lessThan(pix, ivec2(0))) || any(greaterThanEqual(pix, size))) {
+		discard;
+	}
+	float t = texelFetch(text, pix, 0).r;
+	output_color = mix(vec4(0.0, 0.0, 0.0, 0.5), vec4(1.0), t);
+}
@@ -0,0 +1,79 @@
+/*
+	Heads-up display: a text overlay rendered with a built-in bitmap font (font.rs).
+
+	Text is rasterized on the CPU into a single-channel texture,
+	which hud.frag draws over the top-left corner of the window.
+*/
+use crate::font::*;
+use crate::shaders::*;
+use gl::*;
+use gl_obj::*;
+use gl_safe::*;
+
+const GLYPH: u32 = 8; // glyph width and height, in pixels
+
+pub struct Hud {
+	prog: Program,
+	text: Texture,
+	cols: u32,
+	rows: u32,
+}
+
+impl Hud {
+	/// A HUD with room for `rows` lines of `cols` characters,
+	/// drawn in a window of size `viewport`.
+	pub fn new(cols: u32, rows: u32, viewport: uvec2) -> Result<Self, String> {
+		let prog = link(&[
+			//
+			shader!(VERTEX_SHADER, "texture.vert")?,
+			shader!(FRAGMENT_SHADER, "hud.frag")?,
+		])?;
+		prog.set2i("viewport", viewport.0 as i32, viewport.1 as i32);
+		Ok(Self {
+			prog,
+			text: Texture::new2d(R8, uvec2(cols * GLYPH, rows * GLYPH)).filter_nearest(),
+			cols,
+			rows,
+		})
+	}
+
+	/// Replace the displayed text.
+	/// Lines and characters that don't fit are cut off,
+	/// non-printable characters are shown as `?`.
+	pub fn set_text(&self, text: &str) {
+		let (w, h) = (self.cols * GLYPH, self.rows * GLYPH);
+		let mut pix = vec![0u8; (w * h) as usize];
+		for (row, line) in text.lines().take(self.rows as usize).enumerate() {
+			for (col, c) in line.bytes().take(self.cols as usize).enumerate() {
+				let glyph = glyph(c);
+				for (y, bits) in glyph.iter().enumerate() {
+					for x in 0..GLYPH as usize {
+						if bits & (1 << x) != 0 {
+							let px = col * (GLYPH as usize) + x;
+							let py = row * (GLYPH as usize) + y;
+							pix[py * (w as usize) + px] = 255;
+						}
+					}
+				}
+			}
+		}
+		self.text.sub_image2d(0, 0, 0, w, h, gl::RED, gl::UNSIGNED_BYTE, &pix);
+	}
+
+	/// Draw the text over whatever has been drawn already.
+	/// `vao` must hold the full-screen quad used for texture.vert.
+	pub fn draw(&self, vao: VertexArray) {
+		glEnable(gl::BLEND);
+		glBlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+		self.prog.use_program();
+		vao.bind();
+		self.text.bind_texture_unit(0);
+		glDrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+		glDisable(gl::BLEND);
+	}
+}
+
+fn glyph(c: u8) -> &'static [u8; 8] {
+	let i = c.wrapping_sub(FIRST_CHAR) as usize;
+	FONT8X8.get(i).unwrap_or(&FONT8X8[(b'?' - FIRST_CHAR) as usize])
+}
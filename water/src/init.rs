@@ -0,0 +1,158 @@
+/*
+	Initial conditions: the water surface at the start, from --init options.
+
+	Each option gives a shape, its geometry, then optional `key=value` parameters.
+	Shapes add up, without any the water starts flat and at rest:
+
+		# shape  geometry   parameters
+		bump     x y        height=2 radius=20
+		wave                height=0.5 wavelength=40 dir=0 travel=1
+		noise               height=0.5 scale=16
+		image    file.png   height=2
+
+	  * bump:  a gaussian bump at (x, y), `radius` cells wide (a dip for negative heights).
+	  * wave:  a plane wave with crests across direction `dir` (degrees from the x axis).
+			   travel=1 starts it moving along `dir`, travel=0 leaves a standing wave.
+	  * noise: smooth random heights, varying over `scale` cells. Each noise shape is different,
+			   but runs with the same --seed and --init options get the same noise.
+	  * image: heights from a gray image, from 0 (black) to `height` (white), resized to the grid if needed.
+
+	Travelling waves also get a velocity: the rate of change of the height for the wave models,
+	the horizontal flow for the shallow water model.
+	Waves travel 0.5 cells per unit of time (in water of depth 1, see accel.glsl).
+*/
+use crate::spec::*;
+use gl_img::*;
+use gl_obj::*;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use std::f32::consts::PI;
+
+/// Speed of long waves, in cells per unit of time.
+const WAVE_SPEED: f32 = 0.5;
+
+/// Height, velocity and horizontal volume flux of the water surface, cell by cell, row by row.
+pub struct Initial {
+	pub height: Vec<f32>,
+	/// Rate of change of the height.
+	pub vel: Vec<f32>,
+	/// Velocity times depth of the water under travelling waves.
+	flux: Vec<[f32; 2]>,
+	size: uvec2,
+	/// Shared by all noise shapes, so that each gets its own noise.
+	rng: Pcg32,
+}
+
+impl Initial {
+	/// Flat water at rest, on a grid of given size, with noise from the given seed.
+	pub fn new(size: uvec2, seed: u64) -> Self {
+		let n = (size.0 * size.1) as usize;
+		Self {
+			height: vec![0.0; n],
+			vel: vec![0.0; n],
+			flux: vec![[0.0, 0.0]; n],
+			size,
+			rng: Pcg32::seed_from_u64(seed),
+		}
+	}
+
+	/// Shallow water flow (see swe_flow.glsl) in water of given still depth:
+	/// velocities in xy, no mass fluxes yet.
+	pub fn flow(&self, still_depth: f32) -> Vec<[f32; 4]> {
+		self.flux.iter().map(|q| [q[0] / still_depth, q[1] / still_depth, 0.0, 0.0]).collect()
+	}
+
+	/// Add a shape (see top of file for the format).
+	pub fn add(&mut self, text: &str) -> Result<(), String> {
+		let spec = Spec::parse(text)?;
+		let (shape, geometry) = spec.words.split_first().ok_or("want a shape: bump, wave, noise or image")?;
+		let want = |n: usize| {
+			if geometry.len() == n {
+				Ok(())
+			} else {
+				Err(format!("`{}`: want {} arguments, have {}", shape, n, geometry.len()))
+			}
+		};
+
+		let mut height = 1.0;
+		let mut radius = 20.0;
+		let mut wavelength = 40.0;
+		let mut dir = 0.0;
+		let mut travel = 1.0;
+		let mut scale = 16.0;
+		for &(key, value) in &spec.params {
+			match key {
+				"height" => height = value,
+				"radius" => radius = value,
+				"wavelength" => wavelength = value,
+				"dir" => dir = value,
+				"travel" => travel = value,
+				"scale" => scale = value,
+				_ => return Err(format!("unknown parameter `{}`, options: height, radius, wavelength, dir, travel, scale", key)),
+			}
+		}
+
+		let size = self.size;
+		let (w, h) = (size.0 as usize, size.1 as usize);
+		match *shape {
+			"bump" => {
+				want(2)?;
+				let (x0, y0) = (number(geometry[0])?, number(geometry[1])?);
+				for y in 0..h {
+					for x in 0..w {
+						let (dx, dy) = ((x as f32 - x0) / radius, (y as f32 - y0) / radius);
+						self.height[y * w + x] += height * f32::exp(-(dx * dx + dy * dy));
+					}
+				}
+			}
+			"wave" => {
+				want(0)?;
+				let k = 2.0 * PI / wavelength;
+				let (kx, ky) = (k * dir.to_radians().cos(), k * dir.to_radians().sin());
+				for y in 0..h {
+					for x in 0..w {
+						let phase = kx * x as f32 + ky * y as f32;
+						let z = height * phase.cos();
+						self.height[y * w + x] += z;
+						if travel != 0.0 {
+							// h = cos(k.x - ωt), with ω = c k
+							self.vel[y * w + x] += height * WAVE_SPEED * k * phase.sin();
+							let q = &mut self.flux[y * w + x];
+							q[0] += WAVE_SPEED * z * dir.to_radians().cos();
+							q[1] += WAVE_SPEED * z * dir.to_radians().sin();
+						}
+					}
+				}
+			}
+			"noise" => {
+				want(0)?;
+				// value noise: random values on a coarse lattice, wrapping around the grid,
+				// smoothly interpolated in between.
+				let (lw, lh) = (((w as f32) / scale).ceil().max(1.0) as usize, ((h as f32) / scale).ceil().max(1.0) as usize);
+				let rng = &mut self.rng;
+				let lattice: Vec<f32> = (0..lw * lh).map(|_| rng.gen_range(-1.0..1.0)).collect();
+				let at = |i: usize, j: usize| lattice[(j % lh) * lw + (i % lw)];
+				let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+				for y in 0..h {
+					for x in 0..w {
+						let (fx, fy) = (x as f32 / scale, y as f32 / scale);
+						let (i, j) = (fx as usize, fy as usize);
+						let (tx, ty) = (smooth(fx.fract()), smooth(fy.fract()));
+						let top = at(i, j) + tx * (at(i + 1, j) - at(i, j));
+						let bottom = at(i, j + 1) + tx * (at(i + 1, j + 1) - at(i, j + 1));
+						self.height[y * w + x] += height * (top + ty * (bottom - top));
+					}
+				}
+			}
+			"image" => {
+				want(1)?;
+				for (z, g) in self.height.iter_mut().zip(load_gray_image(geometry[0], size)?) {
+					*z += height * g;
+				}
+			}
+			shape => return Err(format!("unknown shape `{}`, options: bump, wave, noise, image", shape)),
+		}
+		Ok(())
+	}
+}
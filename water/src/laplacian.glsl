@@ -0,0 +1,33 @@
+/*
+	5-point Laplacian of src into dst.
+
+	Applied twice (to the height, then to its Laplacian), this gives the biharmonic
+	surface tension term of accel.glsl.
+
+	Cells outside the grid are handled according to boundary.glsl.
+	Nothing flows into or out of walls (obstacles.glsl), the Laplacian of a wall cell is zero.
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, r32f) uniform readonly  image2D src;
+layout (binding = 1, r32f) uniform writeonly image2D dst;
+
+// Difference between cell xy + dxy and the center cell (with value z0).
+float diff(ivec2 xy, ivec2 dxy, float z0){
+	ivec2 c = boundary_cell(xy + dxy, imageSize(src));
+	return is_wall(c) ? 0.0 : imageLoad(src, c).r - z0; // zero outside (fixed boundary)
+}
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+
+	float l = 0.0;
+	if (!is_wall(xy)){
+		float z0 = imageLoad(src, xy).r;
+		l = diff(xy, ivec2(1, 0), z0) + diff(xy, ivec2(-1, 0), z0) + diff(xy, ivec2(0, 1), z0) + diff(xy, ivec2(0, -1), z0);
+	}
+
+	imageStore(dst, xy, vec4(l, 0.0, 0.0, 0.0));
+}
@@ -4,23 +4,76 @@ extern crate gl_win;
 extern crate image;
 extern crate structopt;
 
+#[macro_use]
+mod shaders;
+mod action;
+mod boats;
+mod bodies;
+mod boundary;
+mod current;
+mod font;
+mod hud;
+mod init;
+mod model;
+mod ocean;
+mod particles;
+mod pointers;
+mod rain;
+mod scene;
+mod snapshot;
+mod spec;
+mod stability;
+mod stencil;
+mod tools;
+
+use action::*;
+use boats::*;
+use bodies::*;
+use boundary::*;
+use current::*;
 use gl::*;
 use gl_img::*;
 use gl_obj::*;
 use gl_safe::*;
 use gl_win::*;
-use glutin::event::ElementState;
-use glutin::event::MouseButton;
-use std::cell::Cell;
+use hud::*;
+use init::*;
+use log::{debug, error, info, warn, LevelFilter};
+use model::*;
+use ocean::*;
+use particles::*;
+use pointers::*;
+use rain::*;
+use scene::*;
+use shaders::*;
+use snapshot::*;
+use stability::*;
+use std::path::Path;
 use std::sync::Arc;
 use std::time;
+use stencil::*;
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
+use tools::*;
 
 const MIN_POW: f32 = 0.05;
 const MAX_POW: f32 = 0.2;
+const MAX_STEPS_PER_DRAW: u32 = 64;
+const MIN_BRUSH: f32 = 2.0;
+const MAX_BRUSH: f32 = 400.0;
+const BRUSH_ZOOM: f32 = 1.1; // brush radius factor per scroll wheel line
+const PRESSURE_UNIT: u32 = 4; // image unit for the boats' pressure, see accel.glsl
+const OBSTACLES_UNIT: u32 = 7; // image unit for the wall mask, see obstacles.glsl
+const DAMPING_UNIT: u32 = 5; // image unit for the damping map, see accel.glsl
+const BATHYMETRY_UNIT: u32 = 6; // image unit for the depth map, see accel.glsl
+const MIN_DEPTH: f32 = 0.05; // relative depth of black bathymetry pixels
+const FPS_SMOOTHING: f32 = 0.05; // weight of the latest frame in the smoothed fps
+const HEALTH_CHECK_INTERVAL: u32 = 16; // frames between checks for numerical blow-up
+const OCEAN_TIME: f64 = 0.005; // ocean seconds per simulation time unit
 
 /// OpenGL water simulation.
-#[derive(StructOpt)]
+#[derive(StructOpt, Clone)]
+#[structopt(setting = AppSettings::AllArgsOverrideSelf)]
 struct Cli {
 	/// Image width (pixels).
 	#[structopt(short, long, default_value = "1024")]
@@ -31,25 +84,187 @@ struct Cli {
 	height: u32,
 
 	/// Sky texture file.
+	/// Can be replaced at runtime by shift-dropping an image onto the window.
 	#[structopt(long, default_value = "sky.jpg")]
 	sky: String,
 
 	/// Floor texture file.
+	/// Can be replaced at runtime by dropping an image onto the window.
 	#[structopt(long, default_value = "floor.jpg")]
 	floor: String,
 
+	/// Simulation model: wave (linear wave equation),
+	/// swe (nonlinear shallow water equations)
+	/// or ocean (wave, on top of a swell from a wind wave spectrum; needs a power of two grid size).
+	#[structopt(long, default_value = "wave")]
+	model: Model,
+
+	/// Laplacian stencil for --model wave and ocean: 5 (standard), 9 (isotropic)
+	/// or 4th (fourth order, a cross of 9 points). 9 and 4th keep short ripples round.
+	#[structopt(long, default_value = "5")]
+	stencil: Stencil,
+
+	/// Surface tension for --model wave and ocean: short ripples travel faster than long waves,
+	/// so a drop spreads into a train of rings (capillary wave dispersion).
+	/// 0 for none, try 0.2. Larger values reduce the stable time step.
+	#[structopt(long, default_value = "0")]
+	tension: f32,
+
+	/// Still water depth for --model swe, in units of the surface height.
+	/// Disturbances comparable to the depth make waves nonlinear.
+	#[structopt(long, default_value = "10")]
+	swe_depth: f32,
+
+	/// Wind wave spectrum for --model ocean: phillips or jonswap.
+	#[structopt(long, default_value = "phillips")]
+	spectrum: Spectrum,
+
+	/// Wind speed for --model ocean (m/s). Stronger wind raises longer waves.
+	#[structopt(long, default_value = "6")]
+	wind: f32,
+
+	/// Wind direction for --model ocean (degrees from the x axis).
+	#[structopt(long, default_value = "30")]
+	wind_dir: f32,
+
+	/// Width of the ocean for --model ocean (meters).
+	#[structopt(long, default_value = "200")]
+	ocean_width: f32,
+
+	/// Significant wave height for --model ocean (meters).
+	#[structopt(long, default_value = "1")]
+	wave_height: f32,
+
+	/// Background current carrying the waves along: none, uniform, vortex (a whirlpool)
+	/// or map (velocities from the red and green channels of the --current-map image).
+	#[structopt(long, default_value = "none")]
+	current: Current,
+
+	/// Speed of the --current (pixels per unit of time): of the uniform current,
+	/// at the edge of the vortex core, or of full red or green in the map.
+	#[structopt(long, default_value = "0.3")]
+	current_speed: f32,
+
+	/// Direction of a uniform --current (degrees from the x axis).
+	#[structopt(long, default_value = "0")]
+	current_dir: f32,
+
+	/// Current map image for --current map: red and green are the x and y velocity,
+	/// mid gray is still water. Resized to the grid if needed.
+	#[structopt(long)]
+	current_map: Option<String>,
+
 	/// Damping coefficient.
 	#[structopt(long, default_value = "2e-3")]
 	damping: f32,
 
+	/// Damping map image: the damping coefficient is multiplied by
+	/// 1 + gain * brightness, with the gain set by --damping-map-gain.
+	/// Models reeds, breakwaters or calm zones. Resized to the grid if needed.
+	#[structopt(long)]
+	damping_map: Option<String>,
+
+	/// Damping factor of white pixels in the --damping-map, relative to --damping.
+	#[structopt(long, default_value = "100")]
+	damping_map_gain: f32,
+
 	/// Verlet integration time step.
+	/// Split into substeps if beyond the stability limit.
 	#[structopt(long, default_value = "0.6")]
 	dt: f32,
 
-	/// Radius of mouse disturbance.
+	/// Largest sensible water height: beyond, the simulation is considered
+	/// to blow up and heights are clamped.
+	#[structopt(long, default_value = "1000")]
+	max_height: f32,
+
+	/// Boundary condition at the window edges:
+	/// fixed, reflective, periodic or absorbing.
+	#[structopt(long, default_value = "fixed")]
+	boundary: Boundary,
+
+	/// Thickness of the absorbing boundary layer (cells).
+	#[structopt(long, default_value = "32")]
+	sponge_width: f32,
+
+	/// Obstacle mask image: black pixels are walls (dry land), white pixels water.
+	/// Resized to the grid if needed.
+	#[structopt(long)]
+	obstacles: Option<String>,
+
+	/// Bathymetry (water depth) image: white is --depth deep, black is shallow.
+	/// Waves slow down in shallow water. Resized to the grid if needed.
+	#[structopt(long)]
+	bathymetry: Option<String>,
+
+	/// Initial brush radius of mouse tools and touch disturbances.
+	/// Change with the scroll wheel.
 	#[structopt(long, default_value = "50")]
 	mouse_radius: f32,
 
+	/// Rain: average number of drops per time step, 0 for no rain.
+	#[structopt(long, default_value = "0")]
+	rain: f32,
+
+	/// Smallest raindrop radius.
+	#[structopt(long, default_value = "3")]
+	rain_min_radius: f32,
+
+	/// Largest raindrop radius.
+	#[structopt(long, default_value = "8")]
+	rain_max_radius: f32,
+
+	/// Raindrop strength.
+	#[structopt(long, default_value = "0.5")]
+	rain_strength: f32,
+
+	/// Scene file with scripted wave sources (see scene.rs for the format).
+	#[structopt(long)]
+	scene: Option<String>,
+
+	/// Scripted wave source, in scene file format, e.g. "point 100 200 frequency=0.02".
+	/// May be repeated.
+	#[structopt(long = "source", number_of_values = 1)]
+	sources: Vec<String>,
+
+	/// Initial water surface: bump x y, wave, noise or image file.png, followed by optional
+	/// height=1 radius=20 (bump), wavelength=40 dir=0 travel=1 (wave), scale=16 (noise).
+	/// May be repeated, shapes add up (see init.rs). The water starts flat by default.
+	#[structopt(long = "init", number_of_values = 1)]
+	inits: Vec<String>,
+
+	/// Boat sailing a closed path: waypoints x0 y0 x1 y1 ... followed by optional
+	/// speed=0.4 length=30 width=10 draft=0.5. May be repeated.
+	/// Use --stencil deep for Kelvin wakes.
+	#[structopt(long = "boat", number_of_values = 1)]
+	boats: Vec<String>,
+
+	/// Add a boat steered with the arrow keys.
+	#[structopt(long)]
+	steer: bool,
+
+	/// Floating body: sphere or box, position x y, then optional parameters
+	/// radius=12 (sphere), length=40 width=16 angle=0 (box), height=4 density=0.5 drop=10.
+	/// Heights are in units of surface height. May be repeated, more balls drop with a key.
+	#[structopt(long = "body", number_of_values = 1)]
+	bodies: Vec<String>,
+
+	/// Number of floating leaves and bits of debris drifting with the water.
+	#[structopt(long, default_value = "0")]
+	particles: usize,
+
+	/// Length of the floating particles (pixels).
+	#[structopt(long, default_value = "8")]
+	particle_size: f32,
+
+	/// How fast floating particles take up the flow of --model swe (per unit of time).
+	#[structopt(long, default_value = "0.05")]
+	particle_drag: f32,
+
+	/// Random seed. Runs with the same seed get the same rain and --init noise.
+	#[structopt(long, default_value = "1")]
+	seed: u64,
+
 	/// Water index of refraction.
 	#[structopt(long, default_value = "1.33")]
 	refraction: f32,
@@ -84,148 +299,779 @@ struct Cli {
 
 	/// Sun position x.
 	#[structopt(long, default_value = "0.2")]
+	#[allow(dead_code)] // not used yet, see light_dir in main
 	sun_x: f32,
 
 	/// Sun position y.
 	#[structopt(long, default_value = "0.1")]
+	#[allow(dead_code)] // not used yet, see light_dir in main
 	sun_y: f32,
+
+	/// Key bindings file, overrides the default bindings (see keys.txt).
+	#[structopt(long)]
+	keys: Option<String>,
+
+	/// Snapshot file saved and loaded with a key (see snapshot.rs).
+	#[structopt(long, default_value = "snapshot.bin")]
+	snapshot: String,
+
+	/// Resume a snapshot file, with the options it was saved with.
+	/// Other options given on the command line override those.
+	#[structopt(long)]
+	load: Option<String>,
+
+	/// Log level: off, error, warn, info, debug or trace.
+	#[structopt(long, default_value = "info")]
+	log_level: LevelFilter,
+
+	/// Verbose logging, same as --log-level debug.
+	#[structopt(short, long)]
+	verbose: bool,
+
+	/// Command line options, without the program name and --load, to be saved in snapshots.
+	#[structopt(skip)]
+	argv: Vec<String>,
 }
 
 fn main() {
-	let args = Cli::from_args();
+	let mut args = Cli::from_args();
+	args.argv = options_to_save();
+
+	let level = if args.verbose { LevelFilter::Debug } else { args.log_level };
+	env_logger::Builder::new().filter_level(level).format_timestamp(None).init();
+
+	if let Err(e) = resume(&mut args).and_then(|snapshot| run(args, snapshot)) {
+		error!("{}", e);
+		std::process::exit(1);
+	}
+}
+
+/// Command line options without the program name and --load.
+fn options_to_save() -> Vec<String> {
+	let mut argv = Vec::new();
+	let mut options = std::env::args().skip(1);
+	while let Some(opt) = options.next() {
+		if opt == "--load" {
+			options.next();
+		} else if !opt.starts_with("--load=") {
+			argv.push(opt);
+		}
+	}
+	argv
+}
+
+/// The --load snapshot, if any. Its options are put in front of the command line options.
+fn resume(args: &mut Cli) -> Result<Option<Snapshot>, String> {
+	let fname = match &args.load {
+		None => return Ok(None),
+		Some(fname) => fname.clone(),
+	};
+	let snapshot = Snapshot::load(&fname)?;
+	info!("resuming {} at time step {}: {}", fname, snapshot.time_step, snapshot.args.join(" "));
+	let argv: Vec<String> = snapshot.args.iter().chain(&args.argv).cloned().collect();
+	let program = std::env::args().next().unwrap_or_default();
+	*args = Cli::from_iter_safe(std::iter::once(&program).chain(&argv)).map_err(|e| format!("{}: {}", fname, e.message))?;
+	args.argv = argv;
+	Ok(Some(snapshot))
+}
+
+fn run(mut args: Cli, snapshot: Option<Snapshot>) -> Result<(), String> {
+	if args.sponge_width < 0.0 {
+		return Err(format!("--sponge-width must not be negative, have {}", args.sponge_width));
+	}
+
+	// the ocean is a periodic tile
+	if args.model == Model::Ocean && args.boundary != Boundary::Periodic {
+		info!("--model ocean: using periodic boundary");
+		args.boundary = Boundary::Periodic;
+	}
+
+	let keymap = load_keymap(args.keys.as_deref()).map_err(|e| format!("load key bindings: {}", e))?;
 
 	// window
 	let size = uvec2(args.width, args.height);
 	let (w, h) = (size.0, size.1);
-	let (win, ev) = init_gl_window(w, h, "waves");
+	let (win, ev) = init_gl_window(w, h, "waves")?;
+	check_gl()?;
 
 	// water state
-	let s = State::new(size, &args.sky, &args.floor);
+	let mut s = State::new(&args)?;
+	s.keymap = keymap;
 
 	//let light_dir = vec3(args.sun_x, args.sun_y, 1.0); // TODO: normalize!
 
+	if args.tension != 0.0 && args.model == Model::Swe {
+		return Err(format!("--tension is not supported by --model {}", args.model));
+	}
+
+	// split time steps beyond the stability limit.
+	// for small waves, the staggered shallow water grid behaves like the 5-point stencil.
+	let stable_dt = match args.model {
+		Model::Wave | Model::Ocean => args.stencil.stable_dt(args.tension),
+		Model::Swe => Stencil::Five.stable_dt(0.0),
+	};
+	s.substeps = substeps(args.dt, stable_dt);
+	if s.substeps > 1 {
+		warn!("--dt {} exceeds the stability limit {:.3}, using {} substeps", args.dt, stable_dt, s.substeps);
+	}
+	let dt = args.dt / (s.substeps as f32);
+
 	s.p_accel //
-		.set1f("damping", args.damping);
+		.set1i("boundary", args.boundary as i32)
+		.set1f("tension", args.tension);
+	args.stencil.set_uniforms(s.p_accel);
 
-	s.p_verlet //
-		.set1f("dt", args.dt);
+	s.p_laplacian //
+		.set1i("boundary", args.boundary as i32);
 
 	s.p_mouse //
-		.set1f("mouse_rad", args.mouse_radius);
+		.set1i("drag_flow", (args.model == Model::Swe) as i32);
+
+	s.p_normal //
+		.set1i("boundary", args.boundary as i32);
+
+	s.p_verlet //
+		.set1f("dt", dt);
+
+	s.p_swe_flow //
+		.set1f("dt", dt)
+		.set1f("still_depth", args.swe_depth)
+		.set1i("boundary", args.boundary as i32);
+
+	s.p_advect //
+		.set1f("dt", args.dt)
+		.set1i("boundary", args.boundary as i32);
+
+	s.p_advect_photon //
+		.set1i("boundary", args.boundary as i32);
+
+	s.p_swe_height //
+		.set1f("dt", dt)
+		.set1i("boundary", args.boundary as i32);
 
 	s.p_photon //
 		.set1f("depth", args.depth)
+		.set1i("boundary", args.boundary as i32)
 		.set1f("eta", args.refraction)
 		.set1f("dispersion", args.dispersion / 5.0); // div by 5 because we apply this 5 times (between red,yellow,green,cyan,blue,purple)
-											 //.set("light_dir", vec3(0.03, 0.01, -1.0).normalized())
+											   //.set("light_dir", vec3(0.03, 0.01, -1.0).normalized())
+
+	s.bodies.set_motion(args.dt, 0.25 / args.swe_depth, args.model == Model::Swe);
+	s.particles.set_motion(args.dt, 0.25 / args.swe_depth, args.particle_drag, args.model == Model::Swe);
 
 	s.p_render //
 		.set1f("water_refraction_depth", args.depth)
 		.set1f("water_refraction", args.refraction)
 		.set1f("reflection_height", args.sky_height)
-		.set1f("reflection_strength", args.reflection)
 		//.set1f("light_dir", light_dir)
-		.set1f("sun_strength", args.sun)
-		.set1f("photon_strength", args.caustics)
 		.set1f("ambient", args.ambient);
 
+	s.update_strengths();
+
+	if let Some(snapshot) = snapshot {
+		s.restore(&snapshot).map_err(|e| format!("--load {}: {}", args.load.as_deref().unwrap_or_default(), e))?;
+	}
+
 	// continuously pump redraws
 	let proxy = ev.create_proxy();
-	std::thread::spawn(move || loop {
-		proxy.send_event(()).expect("send event");
-		std::thread::sleep(time::Duration::from_millis(6));
+	std::thread::spawn(move || {
+		while proxy.send_event(()).is_ok() {
+			std::thread::sleep(time::Duration::from_millis(6));
+		}
 	});
 
 	// infinite event loop
 	run_event_loop(ev, win, s);
 }
 
+/// Log GL implementation details and limits,
+/// error out if the compute shaders and direct state access we need are not supported.
+fn check_gl() -> Result<(), String> {
+	let gl = GlInfo::query();
+	info!("GL vendor: {}", gl.vendor);
+	info!("GL renderer: {}", gl.renderer);
+	info!("GL version: {} (GLSL {})", gl.version, gl.glsl_version);
+	gl.require_version(4, 5)?;
+	let limits = GlLimits::query();
+	info!("max texture size: {}", limits.max_texture_size);
+	info!("max image units: {}", limits.max_image_units);
+	info!("max compute work group count: {:?}", limits.max_compute_work_group_count);
+	info!("max compute work group size: {:?}", limits.max_compute_work_group_size);
+	info!("max compute work group invocations: {}", limits.max_compute_work_group_invocations);
+	Ok(())
+}
+
 struct State {
 	p_accel: Program,
+	p_laplacian: Program,
 	p_verlet: Program,
+	p_swe_flow: Program,
+	p_swe_height: Program,
 	p_mouse: Program,
 	p_normal: Program,
 	p_render: Program,
 	p_photon: Program,
 	p_decay: Program,
+	p_paint: Program,
+	p_boats: Program,
+	p_advect: Program,
+	p_advect_photon: Program,
 	pos: Texture,
 	vel: Texture,
+	pos_next: Texture,
+	vel_next: Texture,
 	acc: Texture,
+	lapl: Texture,
+	bilapl: Texture,
+	pressure: Texture,
+	flow: Texture,
+	flow_next: Texture,
 	normal: Texture,
 	photon: Texture,
+	photon_next: Texture,
+	current: Texture,
 	sky: Texture,
 	floor: Texture,
+	obstacles: Texture,
+	damping: Texture,
+	bathymetry: Texture,
+	ocean: Option<Ocean>,
 	vao: VertexArray,
 	time_steps_per_draw: u32,
+	substeps: u32,
+	health: HealthCheck,
+	frames: u32,
 	rand_seed: i32,
-	start: time::Instant,
-	frames: Cell<i32>,
+	last_frame: time::Instant,
+	fps: f32,
+	fps_avg: f32,
+	steps_per_sec: f32,
+	paused: bool,
+	show_caustics: bool,
+	show_reflection: bool,
+	show_sun: bool,
+	pointers: Pointers,
+	tool: Tool,
+	brush_radius: f32,
+	cursor: Option<(f32, f32)>,
+	button: Option<MouseButton>,
+	rain: Rain,
+	scene: Scene,
+	boats: Boats,
+	bodies: Bodies,
+	particles: Particles,
+	time_step: u64,
+	keymap: KeyMap,
+	modifiers: ModifiersState,
+	hud: Hud,
+	show_hud: bool,
+	args: Cli,
 }
 
 impl State {
-	fn new(size: uvec2, sky: &str, floor: &str) -> Self {
-		let p_render = Program::new(&[
+	fn new(args: &Cli) -> Result<Self, String> {
+		let size = uvec2(args.width, args.height);
+		let p_render = link(&[
 			//
-			Shader::new_vert(include_str!("texture.vert")),
-			Shader::new_frag(include_str!("water.frag")),
-		]);
-		Self {
-			p_accel: Self::compute_prog(include_str!("accel.glsl")),
-			p_verlet: Self::compute_prog(include_str!("verlet.glsl")),
-			p_mouse: Self::compute_prog(include_str!("apply_mouse.glsl")),
-			p_normal: Self::compute_prog(include_str!("normal.glsl")),
-			p_decay: Self::compute_prog(include_str!("udecay.glsl")),
-			p_photon: Self::compute_prog(include_str!("photon.glsl")),
+			shader!(VERTEX_SHADER, "texture.vert")?,
+			shader!(FRAGMENT_SHADER, "water.frag")?,
+		])?;
+		let init = Self::initial(args, size)?;
+		Ok(Self {
+			p_accel: link(&[shader!(COMPUTE_SHADER, "accel.glsl", "boundary.glsl", "obstacles.glsl")?])?,
+			p_laplacian: link(&[shader!(COMPUTE_SHADER, "laplacian.glsl", "boundary.glsl", "obstacles.glsl")?])?,
+			p_verlet: link(&[shader!(COMPUTE_SHADER, "verlet.glsl", "obstacles.glsl")?])?,
+			p_swe_flow: link(&[shader!(COMPUTE_SHADER, "swe_flow.glsl", "boundary.glsl", "obstacles.glsl")?])?,
+			p_swe_height: link(&[shader!(COMPUTE_SHADER, "swe_height.glsl", "boundary.glsl", "obstacles.glsl")?])?,
+			p_mouse: link(&[shader!(COMPUTE_SHADER, "apply_mouse.glsl")?])?,
+			p_normal: link(&[shader!(COMPUTE_SHADER, "normal.glsl", "boundary.glsl", "obstacles.glsl")?])?,
+			p_decay: link(&[shader!(COMPUTE_SHADER, "udecay.glsl")?])?,
+			p_paint: link(&[shader!(COMPUTE_SHADER, "paint.glsl")?])?,
+			p_boats: link(&[shader!(COMPUTE_SHADER, "boats.glsl")?])?,
+			p_advect: link(&[shader!(COMPUTE_SHADER, "advect.glsl", "boundary.glsl", "obstacles.glsl")?])?,
+			p_advect_photon: link(&[shader!(COMPUTE_SHADER, "advect_photon.glsl", "boundary.glsl")?])?,
+			p_photon: link(&[shader!(COMPUTE_SHADER, "photon.glsl", "boundary.glsl")?])?,
 			p_render,
-			pos: Texture::new2d(R32F, size),
-			vel: Texture::new2d(R32F, size),
+			pos: Texture::new2d(R32F, size).sub_image2d(0, 0, 0, size.0, size.1, gl::RED, gl::FLOAT, &init.height),
+			vel: Texture::new2d(R32F, size).sub_image2d(0, 0, 0, size.0, size.1, gl::RED, gl::FLOAT, &init.vel),
+			pos_next: Texture::new2d(R32F, size),
+			vel_next: Texture::new2d(R32F, size),
 			acc: Texture::new2d(R32F, size),
-			normal: Texture::new2d(gl::RGBA32F, size),
-			photon: Texture::new2d(gl::RGBA8UI, size).filter_nearest(),
-			sky: load_image(sky).filter_linear().clamp_to_edge(), // TODO !!
-			floor: load_image(floor).filter_linear().mirrored_repeat(),
+			lapl: Texture::new2d(R32F, size),
+			bilapl: Texture::new2d(R32F, size),
+			pressure: Texture::new2d(R32F, size).clear_image(0, gl::RED, gl::FLOAT, &0.0f32),
+			flow: args
+				.boundary
+				.wrap(Texture::new2d(RGBA32F, size).filter_linear())
+				.sub_image2d(0, 0, 0, size.0, size.1, gl::RGBA, gl::FLOAT, &init.flow(args.swe_depth)),
+			flow_next: args.boundary.wrap(Texture::new2d(RGBA32F, size).filter_linear()),
+			normal: args.boundary.wrap(Texture::new2d(gl::RGBA32F, size)),
+			photon: args.boundary.wrap(Texture::new2d(gl::RGBA8UI, size).filter_nearest()),
+			photon_next: args.boundary.wrap(Texture::new2d(gl::RGBA8UI, size).filter_nearest()),
+			current: Self::current(args, size)?,
+			sky: load_image(&args.sky)?.filter_linear().clamp_to_edge(), // TODO !!
+			floor: load_image(&args.floor)?.filter_linear().mirrored_repeat(),
+			obstacles: Self::obstacle_mask(args.obstacles.as_deref(), size)?,
+			damping: Self::damping_map(args, size)?,
+			bathymetry: Self::depth_map(args.bathymetry.as_deref(), size)?,
+			ocean: match args.model {
+				Model::Ocean => Some(Ocean::new(size, args.spectrum, args.wind, args.wind_dir, args.ocean_width, args.wave_height, args.seed)?),
+				_ => None,
+			},
 			vao: Self::vao(p_render),
 			time_steps_per_draw: 6,
+			substeps: 1,
+			health: HealthCheck::new()?,
+			frames: 0,
 			rand_seed: 0,
-			start: time::Instant::now(),
-			frames: Cell::new(0),
-		}
+			last_frame: time::Instant::now(),
+			fps: 0.0,
+			fps_avg: 0.0,
+			steps_per_sec: 0.0,
+			paused: false,
+			show_caustics: true,
+			show_reflection: true,
+			show_sun: true,
+			pointers: Pointers::new(args.mouse_radius),
+			tool: Tool::Push,
+			brush_radius: args.mouse_radius,
+			cursor: None,
+			button: None,
+			scene: Self::scene(args, size)?,
+			boats: Self::boats(args, size)?,
+			bodies: Self::bodies(args, size)?,
+			particles: Particles::new(size, args.boundary, args.particles, args.particle_size, args.seed)?,
+			time_step: 0,
+			rain: Rain::new(size, args.rain, args.rain_min_radius, args.rain_max_radius, args.rain_strength, args.seed),
+			keymap: KeyMap::new(),
+			modifiers: ModifiersState::empty(),
+			hud: Hud::new(44, 20, size)?,
+			show_hud: true,
+			args: args.clone(),
+		})
 	}
 
 	fn steps(&mut self, n: u32) {
-		for _ in 0..n {
-			self.update_acc();
-			self.update_pos_vel();
-			self.apply_mouse();
+		for i in 0..n {
+			self.update_pressure();
+			for _ in 0..self.substeps {
+				match self.args.model {
+					Model::Wave | Model::Ocean => {
+						self.update_acc();
+						self.update_pos_vel();
+					}
+					Model::Swe => self.update_swe(),
+				}
+			}
+			self.advect();
+			self.apply_mouse(i, n);
+			self.apply_rain();
+			self.apply_oscillators();
+			self.particles.advance(self.surface(), self.flow, self.current, self.obstacles, self.time_step as i32);
+			self.time_step += 1;
+		}
+		self.pointers.end_frame();
+		if let Some(ocean) = &self.ocean {
+			ocean.update((OCEAN_TIME * (self.time_step as f64) * (self.args.dt as f64)) as f32, self.pos);
 		}
 		self.update_normal();
+		self.particles.draw_sprites();
 		self.rand_seed += 1;
 		self.p_photon.set1i("rand_seed", self.rand_seed);
+		self.advect_photon(n);
 		self.update_photon();
 	}
 
+	/// Scripted sources from the --scene file and --source options.
+	fn scene(args: &Cli, size: uvec2) -> Result<Scene, String> {
+		let mut scene = Scene::new(size);
+		if let Some(fname) = &args.scene {
+			scene.load(fname)?;
+		}
+		for src in &args.sources {
+			scene.extend(src).map_err(|e| format!("--source {}: {}", src, e))?;
+		}
+		if !scene.is_empty() {
+			info!("{} scripted sources", scene.len());
+		}
+		Ok(scene)
+	}
+
+	/// Initial water surface from the --init options.
+	fn initial(args: &Cli, size: uvec2) -> Result<Initial, String> {
+		let mut init = Initial::new(size, args.seed);
+		for shape in &args.inits {
+			init.add(shape).map_err(|e| format!("--init {}: {}", shape, e))?;
+		}
+		if !args.inits.is_empty() {
+			info!("initial surface: {}", args.inits.join(", "));
+		}
+		Ok(init)
+	}
+
+	/// Background current velocities from the --current options.
+	fn current(args: &Cli, size: uvec2) -> Result<Texture, String> {
+		if let Some(fname) = &args.current_map {
+			info!("current map: {}", fname);
+		}
+		let v = args.current.velocities(size, args.current_speed, args.current_dir, args.current_map.as_deref())?;
+		Ok(Texture::new2d(RG32F, size).sub_image2d(0, 0, 0, size.0, size.1, gl::RG, gl::FLOAT, &v))
+	}
+
+	/// Boats from the --boat and --steer options.
+	fn boats(args: &Cli, size: uvec2) -> Result<Boats, String> {
+		let mut boats = Boats::new(size);
+		for path in &args.boats {
+			boats.add_path(path).map_err(|e| format!("--boat {}: {}", path, e))?;
+		}
+		if args.steer {
+			boats.add_steered()?;
+		}
+		Ok(boats)
+	}
+
+	/// Floating bodies from the --body options.
+	fn bodies(args: &Cli, size: uvec2) -> Result<Bodies, String> {
+		let mut bodies = Bodies::new(size, args.boundary)?;
+		for body in &args.bodies {
+			bodies.add(body).map_err(|e| format!("--body {}: {}", body, e))?;
+		}
+		Ok(bodies)
+	}
+
+	/// Wall mask from image file `fname` (1: wall, 0: water),
+	/// or no walls at all if there is no file.
+	fn obstacle_mask(fname: Option<&str>, size: uvec2) -> Result<Texture, String> {
+		let mut mask = vec![0.0f32; (size.0 * size.1) as usize];
+		if let Some(fname) = fname {
+			info!("obstacles: {}", fname);
+			let gray = load_gray_image(fname, size)?;
+			for (m, g) in mask.iter_mut().zip(gray) {
+				*m = if g < 0.5 { 1.0 } else { 0.0 };
+			}
+		}
+		// uploaded as floats: rows of bytes would be padded to 4 bytes (GL_UNPACK_ALIGNMENT)
+		Ok(Texture::new2d(R8, size)
+			.filter_nearest()
+			.clamp_to_edge()
+			.sub_image2d(0, 0, 0, size.0, size.1, gl::RED, gl::FLOAT, &mask))
+	}
+
+	/// Local damping coefficient: --damping, multiplied by the --damping-map if any,
+	/// plus the sponge layer of an absorbing boundary.
+	fn damping_map(args: &Cli, size: uvec2) -> Result<Texture, String> {
+		let factor = match &args.damping_map {
+			None => vec![1.0; (size.0 * size.1) as usize],
+			Some(fname) => {
+				info!("damping map: {}", fname);
+				load_gray_image(fname, size)?.into_iter().map(|g| 1.0 + args.damping_map_gain * g).collect()
+			}
+		};
+		let mut damping = Vec::with_capacity(factor.len());
+		for y in 0..size.1 {
+			for x in 0..size.0 {
+				let f = factor[(y * size.0 + x) as usize];
+				damping.push(args.damping * f + args.boundary.sponge(x, y, size, args.sponge_width));
+			}
+		}
+		Ok(Texture::new2d(R32F, size)
+			.filter_nearest()
+			.clamp_to_edge()
+			.sub_image2d(0, 0, 0, size.0, size.1, gl::RED, gl::FLOAT, &damping))
+	}
+
+	/// Relative water depth (between MIN_DEPTH and 1) from image file `fname`,
+	/// or uniformly 1 if there is no file.
+	fn depth_map(fname: Option<&str>, size: uvec2) -> Result<Texture, String> {
+		let depth = match fname {
+			None => vec![1.0; (size.0 * size.1) as usize],
+			Some(fname) => {
+				info!("bathymetry: {}", fname);
+				load_gray_image(fname, size)?.into_iter().map(|g| f32::max(g, MIN_DEPTH)).collect()
+			}
+		};
+		Ok(Texture::new2d(R32F, size)
+			.filter_linear()
+			.clamp_to_edge()
+			.sub_image2d(0, 0, 0, size.0, size.1, gl::RED, gl::FLOAT, &depth))
+	}
+
+	/// Snapshot of the water and the maps painted with the mouse tools (see snapshot.rs).
+	fn snapshot(&self) -> Snapshot {
+		let size = uvec2(self.args.width, self.args.height);
+		let n = (size.0 * size.1) as usize;
+		let read = |t: Texture| {
+			let mut v = vec![0.0f32; n];
+			t.get_image(0, gl::RED, gl::FLOAT, &mut v);
+			v
+		};
+		let mut flow = vec![[0.0f32; 4]; n];
+		self.flow.get_image(0, gl::RGBA, gl::FLOAT, &mut flow);
+		Snapshot {
+			args: self.args.argv.clone(),
+			size,
+			time_step: self.time_step,
+			pos: read(self.pos),
+			vel: read(self.vel),
+			flow,
+			// as floats: rows of bytes may be padded
+			obstacles: read(self.obstacles).into_iter().map(|m| (m * 255.0).round() as u8).collect(),
+			bathymetry: read(self.bathymetry),
+			damping: read(self.damping),
+		}
+	}
+
+	/// Continue from a snapshot of the same grid size.
+	fn restore(&mut self, snapshot: &Snapshot) -> Result<(), String> {
+		let (w, h) = (self.args.width, self.args.height);
+		if (snapshot.size.0, snapshot.size.1) != (w, h) {
+			return Err(format!("snapshot grid is {}x{}, want {}x{}", snapshot.size.0, snapshot.size.1, w, h));
+		}
+		self.pos.sub_image2d(0, 0, 0, w, h, gl::RED, gl::FLOAT, &snapshot.pos);
+		self.vel.sub_image2d(0, 0, 0, w, h, gl::RED, gl::FLOAT, &snapshot.vel);
+		self.flow.sub_image2d(0, 0, 0, w, h, gl::RGBA, gl::FLOAT, &snapshot.flow);
+		let obstacles: Vec<f32> = snapshot.obstacles.iter().map(|&m| m as f32 / 255.0).collect();
+		self.obstacles.sub_image2d(0, 0, 0, w, h, gl::RED, gl::FLOAT, &obstacles);
+		self.bathymetry.sub_image2d(0, 0, 0, w, h, gl::RED, gl::FLOAT, &snapshot.bathymetry);
+		self.damping.sub_image2d(0, 0, 0, w, h, gl::RED, gl::FLOAT, &snapshot.damping);
+		self.time_step = snapshot.time_step;
+		self.photon.clear_image(0, gl::RGBA_INTEGER, gl::UNSIGNED_BYTE, &[0u8; 4]);
+		self.steps(0);
+		Ok(())
+	}
+
+	/// Save a snapshot to the --snapshot file.
+	fn save_snapshot(&self) {
+		let fname = &self.args.snapshot;
+		let unsaved = [
+			("rain", self.rain.is_raining()),
+			("bodies", !self.bodies.is_empty()),
+			("boats", !self.boats.is_empty()),
+			("particles", self.particles.len() != 0),
+		];
+		let unsaved: Vec<&str> = unsaved.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect();
+		if !unsaved.is_empty() {
+			warn!("not saving {}: snapshots do not hold {}, the run would not resume as it is", fname, unsaved.join(", "));
+			return;
+		}
+		match self.snapshot().save(fname) {
+			Ok(()) => info!("saved {} at time step {}", fname, self.time_step),
+			Err(e) => error!("{}", e),
+		}
+	}
+
+	/// Continue from the --snapshot file, with the options of this run
+	/// (--load resumes with the options of the snapshot).
+	fn load_snapshot(&mut self) {
+		let fname = self.args.snapshot.clone();
+		match Snapshot::load(&fname).and_then(|snapshot| self.restore(&snapshot).map_err(|e| format!("load snapshot {}: {}", fname, e))) {
+			Ok(()) => info!("loaded {} at time step {}", fname, self.time_step),
+			Err(e) => error!("{}", e),
+		}
+	}
+
+	/// Flatten the water surface and bring it to rest.
+	fn reset(&mut self) {
+		self.pos.clear_image(0, gl::RED, gl::FLOAT, &0.0f32);
+		self.vel.clear_image(0, gl::RED, gl::FLOAT, &0.0f32);
+		self.flow.clear_image(0, gl::RGBA, gl::FLOAT, &[0.0f32; 4]);
+		self.photon.clear_image(0, gl::RGBA_INTEGER, gl::UNSIGNED_BYTE, &[0u8; 4]);
+		self.particles.scatter();
+		self.steps(0);
+	}
+
+	/// Recover from numerical blow-up: reset on NaN or Inf, clamp runaway heights.
+	fn check_health(&mut self) {
+		let health = self.health.check(self.pos);
+		if health.non_finite > 0 {
+			warn!("{} cells with non-finite height, resetting", health.non_finite);
+			self.reset();
+		} else if health.max_height() > self.args.max_height {
+			warn!("height {} exceeds --max-height {}, clamping", health.max_height(), self.args.max_height);
+			self.health.clamp(self.pos, self.vel, self.args.max_height);
+		}
+	}
+
+	/// Set the caustics, reflection and sun strength uniforms,
+	/// zero when toggled off.
+	fn update_strengths(&self) {
+		let on = |show: bool, v: f32| if show { v } else { 0.0 };
+		self.p_render //
+			.set1f("photon_strength", on(self.show_caustics, self.args.caustics))
+			.set1f("reflection_strength", on(self.show_reflection, self.args.reflection))
+			.set1f("sun_strength", on(self.show_sun, self.args.sun));
+	}
+
 	fn update_acc(&self) {
+		if self.args.tension != 0.0 {
+			self.update_bilaplacian();
+		}
 		self.pos.bind_image_unit(0, READ_ONLY);
 		self.vel.bind_image_unit(1, READ_ONLY);
 		self.acc.bind_image_unit(2, WRITE_ONLY);
+		self.bilapl.bind_image_unit(3, READ_ONLY);
+		self.pressure.bind_image_unit(PRESSURE_UNIT, READ_ONLY);
+		self.damping.bind_image_unit(DAMPING_UNIT, READ_ONLY);
+		self.bathymetry.bind_image_unit(BATHYMETRY_UNIT, READ_ONLY);
+		self.obstacles.bind_image_unit(OBSTACLES_UNIT, READ_ONLY);
 		self.exec(self.p_accel)
 	}
 
+	/// Laplacian of the Laplacian of the height, for the surface tension term.
+	fn update_bilaplacian(&self) {
+		self.obstacles.bind_image_unit(OBSTACLES_UNIT, READ_ONLY);
+		for (src, dst) in [(self.pos, self.lapl), (self.lapl, self.bilapl)] {
+			src.bind_image_unit(0, READ_ONLY);
+			dst.bind_image_unit(1, WRITE_ONLY);
+			self.exec(self.p_laplacian);
+		}
+	}
+
 	fn update_pos_vel(&self) {
 		self.pos.bind_image_unit(0, READ_WRITE);
 		self.vel.bind_image_unit(1, READ_WRITE);
 		self.acc.bind_image_unit(2, READ_ONLY);
+		self.obstacles.bind_image_unit(OBSTACLES_UNIT, READ_ONLY);
 		self.exec(self.p_verlet)
 	}
 
-	fn apply_mouse(&self) {
+	/// One shallow water time step: new flow from the old one, then move the water.
+	fn update_swe(&mut self) {
+		self.flow.bind_texture_unit(0);
+		self.pos.bind_image_unit(0, READ_ONLY);
+		self.flow_next.bind_image_unit(1, WRITE_ONLY);
+		self.pressure.bind_image_unit(PRESSURE_UNIT, READ_ONLY);
+		self.damping.bind_image_unit(DAMPING_UNIT, READ_ONLY);
+		self.bathymetry.bind_image_unit(BATHYMETRY_UNIT, READ_ONLY);
+		self.obstacles.bind_image_unit(OBSTACLES_UNIT, READ_ONLY);
+		self.exec(self.p_swe_flow);
+		std::mem::swap(&mut self.flow, &mut self.flow_next);
+
+		self.pos.bind_image_unit(0, READ_WRITE);
+		self.flow.bind_image_unit(1, READ_ONLY);
+		self.exec(self.p_swe_height)
+	}
+
+	/// Carry the water along the background current for one time step:
+	/// the height and the velocity (wave models) or flow (shallow water).
+	fn advect(&mut self) {
+		if self.args.current == Current::None {
+			return;
+		}
+		self.current.bind_image_unit(0, READ_ONLY);
+		self.obstacles.bind_image_unit(OBSTACLES_UNIT, READ_ONLY);
+		self.advect_field(self.pos, self.pos_next);
+		std::mem::swap(&mut self.pos, &mut self.pos_next);
+		if self.args.model == Model::Swe {
+			self.advect_field(self.flow, self.flow_next);
+			std::mem::swap(&mut self.flow, &mut self.flow_next);
+		} else {
+			self.advect_field(self.vel, self.vel_next);
+			std::mem::swap(&mut self.vel, &mut self.vel_next);
+		}
+	}
+
+	fn advect_field(&self, src: Texture, dst: Texture) {
+		src.bind_texture_unit(0);
+		dst.bind_image_unit(1, WRITE_ONLY);
+		self.exec(self.p_advect)
+	}
+
+	/// Carry the caustics along the background current for the `n` time steps of a frame.
+	fn advect_photon(&mut self, n: u32) {
+		if self.args.current == Current::None || n == 0 {
+			return;
+		}
+		self.p_advect_photon.set1f("dt", (n as f32) * self.args.dt);
+		self.photon.bind_texture_unit(0);
+		self.current.bind_image_unit(0, READ_ONLY);
+		self.photon_next.bind_image_unit(1, WRITE_ONLY);
+		self.exec(self.p_advect_photon);
+		std::mem::swap(&mut self.photon, &mut self.photon_next);
+	}
+
+	/// Sail the boats and move the floating bodies for one time step,
+	/// pressing the hulls and the bodies into the pressure field.
+	fn update_pressure(&mut self) {
+		if self.boats.is_empty() && self.bodies.is_empty() {
+			return;
+		}
+		self.boats.advance(self.args.dt);
+		self.boats.bind(2);
+		self.p_boats.set1i("num_boats", self.boats.len() as i32);
+		self.pressure.bind_image_unit(0, WRITE_ONLY);
+		self.exec(self.p_boats);
+		self.bodies.advance(self.surface(), self.pressure, self.flow, self.current, self.obstacles);
+	}
+
+	/// Apply the pointer wakes for time step `i` out of `n` in this frame.
+	fn apply_mouse(&self, i: u32, n: u32) {
+		if self.pointers.is_empty() {
+			return;
+		}
+		let num = self.pointers.sweep(i, n, self.args.dt);
+		self.pointers.bind(0);
+		self.apply_sources(num);
+	}
+
+	fn apply_rain(&mut self) {
+		let n = self.rain.fall();
+		if n == 0 {
+			return;
+		}
+		self.rain.bind(0);
+		self.apply_sources(n);
+	}
+
+	fn apply_oscillators(&self) {
+		if self.scene.is_empty() {
+			return;
+		}
+		self.scene.upload((self.time_step as f64) * (self.args.dt as f64));
+		self.scene.bind(0);
+		self.apply_sources(self.scene.len());
+	}
+
+	/// Apply the current tool under the cursor while a mouse button is held
+	/// (except for push, which works through the pointers).
+	fn paint(&self) {
+		let (center, button) = match (self.cursor, self.button) {
+			(Some(c), Some(b)) if self.tool != Tool::Push => (c, b),
+			_ => return,
+		};
+		let sign = if button == MouseButton::Right { -1.0 } else { 1.0 };
+		self.p_paint //
+			.set1i("tool", self.tool as i32)
+			.set2f("center", center.0, center.1)
+			.set1f("radius", self.brush_radius)
+			.set1f("direction", sign)
+			.set1f("min_depth", MIN_DEPTH);
+		self.damping.bind_image_unit(0, READ_WRITE);
+		self.obstacles.bind_image_unit(2, READ_WRITE);
+		self.bathymetry.bind_image_unit(3, READ_WRITE);
+		self.exec(self.p_paint)
+	}
+
+	/// Apply `n` disturbance sources from the buffer bound to shader storage block 0.
+	fn apply_sources(&self, n: usize) {
+		if n == 0 {
+			return;
+		}
+		self.p_mouse.set1i("num_sources", n as i32);
 		self.pos.bind_image_unit(0, READ_WRITE);
+		self.flow.bind_image_unit(1, READ_WRITE);
 		self.exec(self.p_mouse)
 	}
 
+	/// Height of the water surface: the height field, plus the swell for the ocean model.
+	fn surface(&self) -> Texture {
+		self.ocean.as_ref().map_or(self.pos, |o| o.surface())
+	}
+
 	fn update_normal(&self) {
-		self.pos.bind_image_unit(0, READ_ONLY);
+		self.surface().bind_image_unit(0, READ_ONLY);
 		self.normal.bind_image_unit(1, WRITE_ONLY);
+		self.obstacles.bind_image_unit(OBSTACLES_UNIT, READ_ONLY);
 		self.exec(self.p_normal);
 	}
 
@@ -233,8 +1079,9 @@ impl State {
 		self.photon.bind_image_unit(0, READ_WRITE);
 		self.exec(self.p_decay);
 
-		self.normal.bind_image_unit(0, READ_ONLY);
+		self.normal.bind_texture_unit(0);
 		self.photon.bind_image_unit(1, READ_WRITE);
+		self.bathymetry.bind_texture_unit(2);
 		self.exec(self.p_photon);
 	}
 
@@ -242,14 +1089,118 @@ impl State {
 		glClearColor(0.5, 0.5, 0.5, 1.0);
 		glClear(gl::COLOR_BUFFER_BIT);
 
+		let (x, y) = self.cursor.unwrap_or((0.0, 0.0));
+		let radius = if self.cursor.is_some() { self.brush_radius } else { 0.0 };
+		let color = self.tool.color();
+		self.p_render //
+			.set3f("brush", x, y, radius)
+			.set3f("brush_color", color.0, color.1, color.2)
+			.set1i("num_boats", self.boats.len() as i32)
+			.set1i("num_bodies", self.bodies.len() as i32);
+
 		self.p_render.use_program();
+		self.boats.bind(2);
+		self.bodies.bind(4);
 		self.vao.bind();
 		self.normal.bind_texture_unit(0);
 		self.sky.bind_texture_unit(1);
 		self.floor.bind_texture_unit(2);
 		self.photon.bind_texture_unit(3);
+		self.obstacles.bind_texture_unit(4);
+		self.bathymetry.bind_texture_unit(5);
+		self.particles.surface().bind_texture_unit(6);
+		self.particles.debris().bind_texture_unit(7);
 
 		glDrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+
+		if self.show_hud {
+			self.hud.set_text(&self.hud_text());
+			self.hud.draw(self.vao);
+		}
+	}
+
+	/// Update the frame rate statistics after a frame with `steps` time steps.
+	fn update_stats(&mut self, steps: u32) {
+		let now = time::Instant::now();
+		let secs = (now - self.last_frame).as_secs_f32();
+		self.last_frame = now;
+		if secs == 0.0 {
+			return;
+		}
+		let smooth = |avg: f32, v: f32| if avg == 0.0 { v } else { avg + FPS_SMOOTHING * (v - avg) };
+		self.fps = 1.0 / secs;
+		self.fps_avg = smooth(self.fps_avg, self.fps);
+		self.steps_per_sec = smooth(self.steps_per_sec, (steps as f32) / secs);
+	}
+
+	fn hud_text(&self) -> String {
+		let a = &self.args;
+		let on = |show: bool, v: f32| if show { format!("{}", v) } else { "off".into() };
+		let size = self.pos.size();
+		format!(
+			"fps        {:.0} (avg {:.1})\n\
+			 steps/s    {:.0}\n\
+			 steps/draw {}{}\n\
+			 model      {}\n\
+			 grid       {} x {}\n\
+			 dt         {}{}\n\
+			 damping    {}{}\n\
+			 current    {}\n\
+			 boundary   {}\n\
+			 tool       {} (brush {:.0})\n\
+			 rain       {}\n\
+			 boats      {}\n\
+			 bodies     {}\n\
+			 particles  {}\n\
+			 refraction {} (dispersion {})\n\
+			 depth      {}\n\
+			 caustics   {}\n\
+			 reflection {}\n\
+			 sun        {}",
+			self.fps,
+			self.fps_avg,
+			self.steps_per_sec,
+			self.time_steps_per_draw,
+			if self.paused { " (paused)" } else { "" },
+			self.model_text(),
+			size.0,
+			size.1,
+			a.dt,
+			if self.substeps > 1 { format!(" ({} substeps)", self.substeps) } else { "".into() },
+			a.damping,
+			if a.damping_map.is_some() { " (map)" } else { "" },
+			match a.current {
+				Current::None => "none".into(),
+				c => format!("{} (speed {})", c, a.current_speed),
+			},
+			a.boundary,
+			self.tool,
+			self.brush_radius,
+			if self.rain.is_raining() { format!("{} drops/step", a.rain) } else { "off".into() },
+			match self.boats.steered_speed() {
+				Some(speed) => format!("{} (steered speed {:.2})", self.boats.len(), speed),
+				None => format!("{}", self.boats.len()),
+			},
+			self.bodies.len(),
+			self.particles.len(),
+			a.refraction,
+			a.dispersion,
+			a.depth,
+			on(self.show_caustics, a.caustics),
+			on(self.show_reflection, a.reflection),
+			on(self.show_sun, a.sun),
+		)
+	}
+
+	/// The model and its main parameters.
+	fn model_text(&self) -> String {
+		let a = &self.args;
+		match a.model {
+			Model::Wave if a.tension != 0.0 => format!("wave (stencil {}, tension {})", a.stencil, a.tension),
+			Model::Wave => format!("wave (stencil {})", a.stencil),
+			Model::Swe => format!("swe (depth {})", a.swe_depth),
+			Model::Ocean => format!("ocean ({}, wind {})", a.spectrum, a.wind),
+		}
 	}
 
 	fn exec(&self, p: Program) {
@@ -257,50 +1208,147 @@ impl State {
 		p.compute_and_sync(uvec3(xy.0, xy.1, 1))
 	}
 
-	fn on_cursor_moved(&self, position: (f64, f64)) {
+	fn on_cursor_moved(&mut self, position: (f64, f64)) {
 		let (w, h) = (self.pos.size().0, self.pos.size().1);
 		let (x, y) = ((position.0) as i32, (position.1) as i32);
 		if x >= 0 && x < (w as i32) && y >= 0 && y < (h as i32) {
-			self.p_mouse.set2i("mouse_pos", x, y);
+			self.pointers.move_to(PointerId::Mouse, x as f32, y as f32);
+			self.cursor = Some((x as f32, y as f32));
 		}
 	}
 
-	fn on_mouse_input(&self, button: MouseButton, state: ElementState) {
-		let sign = match button {
-			glutin::event::MouseButton::Right => -1.0,
-			_ => 1.0,
+	fn on_mouse_input(&mut self, button: MouseButton, state: ElementState) {
+		self.button = match state {
+			ElementState::Pressed => Some(button),
+			ElementState::Released => None,
 		};
-		let pow = match state {
-			ElementState::Pressed => MAX_POW,
-			ElementState::Released => MIN_POW,
+		self.update_mouse_pow();
+	}
+
+	/// The push tool disturbs the water through the mouse pointer,
+	/// weakly when just hovering. Other tools don't.
+	fn update_mouse_pow(&mut self) {
+		let pow = match (self.tool, self.button) {
+			(Tool::Push, Some(MouseButton::Right)) => -MAX_POW,
+			(Tool::Push, Some(_)) => MAX_POW,
+			(Tool::Push, None) => MIN_POW,
+			_ => 0.0,
+		};
+		self.pointers.set_pow(PointerId::Mouse, pow);
+	}
+
+	/// The scroll wheel changes the brush size.
+	fn on_mouse_wheel(&mut self, delta: MouseScrollDelta) {
+		let lines = match delta {
+			MouseScrollDelta::LineDelta(_, y) => y,
+			MouseScrollDelta::PixelDelta(p) => (p.y / 20.0) as f32,
 		};
-		self.p_mouse.set1f("mouse_pow", sign * pow);
+		self.brush_radius = (self.brush_radius * BRUSH_ZOOM.powf(lines)).clamp(MIN_BRUSH, MAX_BRUSH);
+		self.pointers.set_radius(self.brush_radius);
+	}
+
+	/// Each finger on a touch screen disturbs the water like a pressed mouse button.
+	fn on_touch(&mut self, touch: Touch) {
+		let id = PointerId::Touch(touch.id);
+		match touch.phase {
+			TouchPhase::Started | TouchPhase::Moved => {
+				self.pointers.move_to(id, touch.location.x as f32, touch.location.y as f32);
+				self.pointers.set_pow(id, MAX_POW);
+			}
+			TouchPhase::Ended | TouchPhase::Cancelled => self.pointers.remove(id),
+		}
 	}
 
 	fn on_redraw_requested(&mut self, win: &Window) {
-		self.draw(&win);
+		self.draw(win);
 		win.swap_buffers().unwrap();
-		self.steps(self.time_steps_per_draw);
-		self.frames.set(self.frames.get() + 1);
-		let secs = self.start.elapsed().as_secs_f32();
-		let fps = self.frames.get() as f32 / secs;
-		dbg!(fps);
+		self.paint();
+		let steps = if self.paused { 0 } else { self.time_steps_per_draw };
+		self.steps(steps);
+		self.update_stats(steps);
+		self.frames += 1;
+		if steps > 0 && self.frames.is_multiple_of(HEALTH_CHECK_INTERVAL) {
+			self.check_health();
+		}
+	}
+
+	fn action(&self, input: KeyboardInput) -> Option<Action> {
+		self.keymap.action(input).and_then(Action::from_name)
+	}
+
+	fn on_action(&mut self, action: Action) {
+		use Action::*;
+		debug!("action: {:?}", action);
+		match action {
+			Pause => self.paused = !self.paused,
+			Step => self.steps(1),
+			Reset => self.reset(),
+			Faster => self.time_steps_per_draw = u32::min(self.time_steps_per_draw + 1, MAX_STEPS_PER_DRAW),
+			Slower => self.time_steps_per_draw = u32::max(self.time_steps_per_draw - 1, 1),
+			ToggleCaustics => self.show_caustics = !self.show_caustics,
+			ToggleReflection => self.show_reflection = !self.show_reflection,
+			ToggleSun => self.show_sun = !self.show_sun,
+			ToggleHud => self.show_hud = !self.show_hud,
+			ToolPush => self.tool = Tool::Push,
+			ToolCalm => self.tool = Tool::Calm,
+			ToolWall => self.tool = Tool::Wall,
+			ToolDepth => self.tool = Tool::Depth,
+			BoatFaster => self.boats.throttle(1.0),
+			BoatSlower => self.boats.throttle(-1.0),
+			BoatLeft => self.boats.turn(-1.0),
+			BoatRight => self.boats.turn(1.0),
+			DropBall => {
+				if let Some((x, y)) = self.cursor {
+					self.bodies.drop_ball(x, y)
+				}
+			}
+			SaveSnapshot => self.save_snapshot(),
+			LoadSnapshot => self.load_snapshot(),
+			Quit => (),
+		}
+		self.update_strengths();
+		if self.cursor.is_some() {
+			self.update_mouse_pow();
+		}
 	}
 
 	fn on_user_event(&self, win: &Window) {
 		win.window().request_redraw()
 	}
 
-	fn on_cursor_entered(&self) {
-		self.p_mouse.set1f("mouse_pow", MIN_POW);
+	fn on_cursor_entered(&mut self) {
+		self.update_mouse_pow();
+	}
+
+	fn on_cursor_left(&mut self) {
+		self.pointers.remove(PointerId::Mouse);
+		self.cursor = None;
+		self.button = None;
 	}
 
-	fn on_cursor_left(&self) {
-		self.p_mouse.set1f("mouse_pow", 0.0);
+	fn on_modifiers_changed(&mut self, modifiers: ModifiersState) {
+		self.modifiers = modifiers;
 	}
 
-	fn compute_prog(src: &str) -> Program {
-		Program::new(&[Shader::new_comp(src)])
+	/// Replace the floor texture by a dropped image file,
+	/// or the sky texture when shift is held.
+	fn on_dropped_file(&mut self, path: &Path) {
+		let fname = path.to_string_lossy();
+		let tex = match load_image(&fname) {
+			Ok(tex) => tex.filter_linear(),
+			Err(e) => return error!("{}", e),
+		};
+		if self.modifiers.shift() {
+			info!("sky: {}", fname);
+			self.sky.delete();
+			self.sky = tex.clamp_to_edge();
+			self.args.sky = fname.into();
+		} else {
+			info!("floor: {}", fname);
+			self.floor.delete();
+			self.floor = tex.mirrored_repeat();
+			self.args.floor = fname.into();
+		}
 	}
 
 	fn vao(prog: Program) -> VertexArray {
@@ -334,18 +1382,26 @@ impl State {
 	}
 }
 
-fn run_event_loop(ev: EventLoop, win: Arc<Window>, mut s: State) {
+fn run_event_loop(ev: EventLoop, win: Arc<Window>, mut s: State) -> ! {
 	ev.run(move |event, _, control_flow| {
 		*control_flow = ControlFlow::Wait;
 		match event {
 			Event::RedrawRequested(_) => s.on_redraw_requested(&win),
 			Event::UserEvent(_) => s.on_user_event(&win),
-			Event::LoopDestroyed => return,
 			Event::WindowEvent { event, .. } => match event {
 				WindowEvent::CursorMoved { position, .. } => s.on_cursor_moved((position.x, position.y)),
 				WindowEvent::MouseInput { state, button, .. } => s.on_mouse_input(button, state),
+				WindowEvent::MouseWheel { delta, .. } => s.on_mouse_wheel(delta),
 				WindowEvent::CursorEntered { .. } => s.on_cursor_entered(),
 				WindowEvent::CursorLeft { .. } => s.on_cursor_left(),
+				WindowEvent::Touch(touch) => s.on_touch(touch),
+				WindowEvent::ModifiersChanged(modifiers) => s.on_modifiers_changed(modifiers),
+				WindowEvent::DroppedFile(path) => s.on_dropped_file(&path),
+				WindowEvent::KeyboardInput { input, .. } => match s.action(input) {
+					Some(Action::Quit) => *control_flow = ControlFlow::Exit,
+					Some(action) => s.on_action(action),
+					None => (),
+				},
 				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
 				_ => (),
 			},
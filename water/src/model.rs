@@ -0,0 +1,39 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Equations the water surface follows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Model {
+	/// Linear wave equation (accel.glsl, verlet.glsl): ripples of small amplitude.
+	Wave,
+	/// Nonlinear shallow water equations (swe_flow.glsl, swe_height.glsl):
+	/// large waves, flowing water, mass conservation.
+	Swe,
+	/// Open ocean swell synthesized from a wind wave spectrum (ocean.rs),
+	/// with local ripples following the wave equation on top.
+	Ocean,
+}
+
+impl FromStr for Model {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"wave" => Ok(Model::Wave),
+			"swe" => Ok(Model::Swe),
+			"ocean" => Ok(Model::Ocean),
+			_ => Err(format!("unknown model `{}`, options: wave, swe, ocean", s)),
+		}
+	}
+}
+
+impl fmt::Display for Model {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			Model::Wave => "wave",
+			Model::Swe => "swe",
+			Model::Ocean => "ocean",
+		};
+		f.write_str(s)
+	}
+}
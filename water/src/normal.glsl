@@ -1,5 +1,8 @@
 /*
 	Calculate the normal vector of a height map.
+
+	Cells outside the grid are handled according to boundary.glsl,
+	walls (obstacles.glsl) are flat and take the height of the center cell.
 */
 
 #version 450 core
@@ -9,14 +12,24 @@ layout (local_size_x = 16, local_size_y = 16) in;
 layout (binding = 0, r32f)    uniform readonly  image2D height;
 layout (binding = 1, rgba32f) uniform writeonly image2D normal;
 
+float load_height(ivec2 xy, float h0){
+	ivec2 c = boundary_cell(xy, imageSize(height));
+	return is_wall(c) ? h0 : imageLoad(height, c).r;
+}
 
 void main(){
 	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
 
-	float hpx = imageLoad(height, xy + ivec2( 0, 1)).r;
-	float hmx = imageLoad(height, xy + ivec2( 0,-1)).r;
-	float hpy = imageLoad(height, xy + ivec2( 1, 0)).r;
-	float hmy = imageLoad(height, xy + ivec2(-1, 0)).r;
+	if (is_wall(xy)){
+		imageStore(normal, xy, vec4(0.0, 0.0, 1.0, 0.0));
+		return;
+	}
+
+	float h0  = imageLoad(height, xy).r;
+	float hpx = load_height(xy + ivec2( 0, 1), h0);
+	float hmx = load_height(xy + ivec2( 0,-1), h0);
+	float hpy = load_height(xy + ivec2( 1, 0), h0);
+	float hmy = load_height(xy + ivec2(-1, 0), h0);
 
 	float partialx = 0.5*(hpx - hmx);
 	float partialy = 0.5*(hpy - hmy);
@@ -24,4 +37,4 @@ void main(){
 	vec3 n = normalize(vec3(-partialx, -partialy, 1.0));
 
 	imageStore(normal, xy, vec4(n, 0.0));
-}
\ No newline at end of file
+}
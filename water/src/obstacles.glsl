@@ -0,0 +1,11 @@
+/*
+	Obstacles: cells that are walls (dry land) instead of water.
+	Waves reflect off walls as off a reflective boundary (see boundary.glsl).
+	Included by shaders that read neighbouring cells.
+*/
+
+layout (binding = 7, r8) uniform readonly image2D obstacles;
+
+bool is_wall(ivec2 xy) {
+	return imageLoad(obstacles, xy).r > 0.5;
+}
@@ -0,0 +1,187 @@
+/*
+	Open ocean: a height field synthesized from a wind wave spectrum, after Tessendorf.
+
+	At startup, random complex amplitudes h0(k) are drawn for each wave vector k,
+	with the variance given by the spectrum (Phillips or JONSWAP) for the wind.
+	Every frame, ocean_spectrum.glsl evolves them to the current time,
+	an inverse FFT (fft.glsl) turns them into the swell,
+	and ocean_height.glsl adds the local wave field (mouse, rain, ...) to get the surface.
+
+	The grid is a periodic ocean tile, whose width and height (in cells) must be powers of two.
+
+	Tessendorf, Simulating Ocean Water, SIGGRAPH course notes (2001).
+*/
+use crate::shaders::*;
+use gl::*;
+use gl_obj::*;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// Gravitational acceleration (m/s²), as in ocean_spectrum.glsl.
+const GRAVITY: f32 = 9.81;
+
+/// Wind wave spectra.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Spectrum {
+	/// Fully developed wind waves (Tessendorf 2001).
+	Phillips,
+	/// Fetch-limited wind sea, more sharply peaked than Phillips,
+	/// with a cos² directional spread (Hasselmann et al. 1973).
+	Jonswap,
+}
+
+impl Spectrum {
+	/// Spectral density at wave vector k (rad/m), up to a constant factor,
+	/// for wind speed `wind` (m/s) blowing along unit vector `dir`.
+	fn density(self, k: (f32, f32), wind: f32, dir: (f32, f32)) -> f32 {
+		let kk = f32::sqrt(k.0 * k.0 + k.1 * k.1);
+		if kk == 0.0 {
+			return 0.0;
+		}
+		let cos = (k.0 * dir.0 + k.1 * dir.1) / kk;
+		match self {
+			Spectrum::Phillips => {
+				let l = wind * wind / GRAVITY; // largest waves raised by the wind
+				f32::exp(-1.0 / (kk * l).powi(2)) / kk.powi(4) * cos * cos
+			}
+			Spectrum::Jonswap => {
+				if cos <= 0.0 {
+					return 0.0;
+				}
+				let w = f32::sqrt(GRAVITY * kk);
+				let wp = 0.855 * GRAVITY / wind; // peak angular frequency
+				let sigma = if w <= wp { 0.07 } else { 0.09 };
+				let peak = f32::exp(-(w - wp).powi(2) / (2.0 * sigma * sigma * wp * wp));
+				let s = f32::exp(-1.25 * (wp / w).powi(4)) / w.powi(5) * f32::powf(3.3, peak);
+				// from frequency to wave vector: S(k) = S(ω) dω/dk / k, with ω² = g k.
+				s * (0.5 * GRAVITY / w) / kk * cos * cos
+			}
+		}
+	}
+}
+
+impl FromStr for Spectrum {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"phillips" => Ok(Spectrum::Phillips),
+			"jonswap" => Ok(Spectrum::Jonswap),
+			_ => Err(format!("unknown spectrum `{}`, options: phillips, jonswap", s)),
+		}
+	}
+}
+
+impl fmt::Display for Spectrum {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			Spectrum::Phillips => "phillips",
+			Spectrum::Jonswap => "jonswap",
+		};
+		f.write_str(s)
+	}
+}
+
+pub struct Ocean {
+	p_spectrum: Program,
+	p_fft: Program,
+	p_height: Program,
+	h0: Texture,
+	ping: Texture,
+	pong: Texture,
+	surface: Texture,
+}
+
+impl Ocean {
+	/// An ocean tile `width` meters wide on a grid of given size,
+	/// with wind speed `wind` (m/s) blowing towards `wind_dir` (degrees from the x axis)
+	/// and significant wave height `wave_height` (m). Runs with the same seed get the same waves.
+	pub fn new(size: uvec2, spectrum: Spectrum, wind: f32, wind_dir: f32, width: f32, wave_height: f32, seed: u64) -> Result<Self, String> {
+		if !size.0.is_power_of_two() || !size.1.is_power_of_two() {
+			return Err(format!("ocean needs a power of two grid size, have {} x {}", size.0, size.1));
+		}
+		let (w, h) = (size.0 as usize, size.1 as usize);
+		let cell = width / (size.0 as f32); // meters
+		let dk = (2.0 * PI / (cell * size.0 as f32), 2.0 * PI / (cell * size.1 as f32));
+		let dir = (wind_dir.to_radians().cos(), wind_dir.to_radians().sin());
+
+		// random amplitudes h0(k), Box-Muller transformed to complex normal deviates.
+		let mut rng = Pcg32::seed_from_u64(seed);
+		let mut h0 = vec![(0.0, 0.0); w * h];
+		for y in 0..h {
+			for x in 0..w {
+				let signed = |i: usize, n: usize| if i < n / 2 { i as f32 } else { i as f32 - n as f32 };
+				let k = (signed(x, w) * dk.0, signed(y, h) * dk.1);
+				let amp = f32::sqrt(spectrum.density(k, wind, dir) / 2.0);
+				let (r, phi) = (f32::sqrt(-2.0 * f32::ln(1.0 - rng.gen::<f32>())), 2.0 * PI * rng.gen::<f32>());
+				h0[y * w + x] = (amp * r * phi.cos(), amp * r * phi.sin());
+			}
+		}
+
+		// scale to the wave height (4 standard deviations), in units of the cell size.
+		let variance: f32 = 2.0 * h0.iter().map(|(re, im)| re * re + im * im).sum::<f32>();
+		let scale = if variance > 0.0 { wave_height / (4.0 * variance.sqrt()) / cell } else { 0.0 };
+
+		// pair each h0(k) with conj(h0(-k)), see ocean_spectrum.glsl.
+		let mut pairs = Vec::with_capacity(w * h);
+		for y in 0..h {
+			for x in 0..w {
+				let (a, b) = (h0[y * w + x], h0[((h - y) % h) * w + (w - x) % w]);
+				pairs.push([scale * a.0, scale * a.1, scale * b.0, -scale * b.1]);
+			}
+		}
+
+		let p_spectrum = link(&[shader!(COMPUTE_SHADER, "ocean_spectrum.glsl")?])?;
+		p_spectrum.set2f("dk", dk.0, dk.1);
+		Ok(Self {
+			p_spectrum,
+			p_fft: link(&[shader!(COMPUTE_SHADER, "fft.glsl")?])?,
+			p_height: link(&[shader!(COMPUTE_SHADER, "ocean_height.glsl")?])?,
+			h0: Texture::new2d(RGBA32F, size).sub_image2d(0, 0, 0, size.0, size.1, gl::RGBA, gl::FLOAT, &pairs),
+			ping: Texture::new2d(RG32F, size),
+			pong: Texture::new2d(RG32F, size),
+			surface: Texture::new2d(R32F, size),
+		})
+	}
+
+	/// Update the surface to the swell at time `t` (seconds) plus the `local` wave field.
+	pub fn update(&self, t: f32, local: Texture) {
+		self.p_spectrum.set1f("t", t);
+		self.h0.bind_image_unit(0, READ_ONLY);
+		self.ping.bind_image_unit(1, WRITE_ONLY);
+		self.exec(self.p_spectrum);
+
+		let (mut src, mut dst) = (self.ping, self.pong);
+		let size = self.h0.size();
+		for (axis, &n) in [size.0, size.1].iter().enumerate() {
+			let mut span = 1;
+			while span < n {
+				self.p_fft.set1i("axis", axis as i32).set1i("span", span as i32);
+				src.bind_image_unit(0, READ_ONLY);
+				dst.bind_image_unit(1, WRITE_ONLY);
+				self.exec(self.p_fft);
+				std::mem::swap(&mut src, &mut dst);
+				span *= 2;
+			}
+		}
+
+		src.bind_image_unit(0, READ_ONLY);
+		local.bind_image_unit(1, READ_ONLY);
+		self.surface.bind_image_unit(2, WRITE_ONLY);
+		self.exec(self.p_height);
+	}
+
+	/// Surface height, as of the last update.
+	pub fn surface(&self) -> Texture {
+		self.surface
+	}
+
+	fn exec(&self, p: Program) {
+		let xy = self.h0.size();
+		p.compute_and_sync(uvec3(xy.0, xy.1, 1))
+	}
+}
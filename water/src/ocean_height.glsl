@@ -0,0 +1,18 @@
+/*
+	Ocean surface height: the swell, which is the real part of the inverse Fourier transform
+	of the ocean spectrum (ocean_spectrum.glsl, fft.glsl) in units of the grid cell size,
+	plus the local wave field, so that splashes ride on the swell.
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, rg32f) uniform readonly  image2D field;
+layout (binding = 1, r32f)  uniform readonly  image2D local;
+layout (binding = 2, r32f)  uniform writeonly image2D surface;
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+	float h = imageLoad(field, xy).x + imageLoad(local, xy).r;
+	imageStore(surface, xy, vec4(h, 0.0, 0.0, 0.0));
+}
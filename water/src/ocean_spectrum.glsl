@@ -0,0 +1,41 @@
+/*
+	Ocean wave spectrum at time t, evolved from the initial spectrum h0 drawn in ocean.rs:
+
+		h(k, t) = h0(k) exp(iωt) + conj(h0(-k)) exp(-iωt),  with ω² = g |k|
+
+	for deep water gravity waves. The second term keeps the height field real.
+	Its inverse Fourier transform (fft.glsl) is the height field.
+
+	Texel (x, y) holds wave vector k = dk * (x, y), where indices beyond half the size
+	wrap around to negative wave vectors.
+
+	Tessendorf, Simulating Ocean Water, SIGGRAPH course notes (2001).
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, rgba32f) uniform readonly  image2D h0;       // h0(k), conj(h0(-k))
+layout (binding = 1, rg32f)   uniform writeonly image2D spectrum;
+
+uniform float t;               // time, in seconds
+uniform vec2  dk;              // wave vector spacing, in radians per meter
+uniform float gravity = 9.81;  // in m/s²
+
+vec2 cmul(vec2 a, vec2 b){
+	return vec2(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+	ivec2 size = imageSize(spectrum);
+
+	ivec2 n = xy - size * ivec2(greaterThanEqual(xy, size / 2));
+	float w = sqrt(gravity * length(vec2(n) * dk));
+	vec2 e = vec2(cos(w * t), sin(w * t));
+
+	vec4 h = imageLoad(h0, xy);
+	vec2 s = cmul(h.xy, e) + cmul(h.zw, vec2(e.x, -e.y));
+
+	imageStore(spectrum, xy, vec4(s, 0.0, 0.0));
+}
@@ -0,0 +1,54 @@
+/*
+	Brush tools (tools.rs): edit the simulation around the cursor.
+
+	  * calm:  add damping to the damping map (direction > 0), or remove it (direction < 0).
+	  * wall:  add walls to the obstacle mask (direction > 0), or remove them (direction < 0).
+	  * depth: make the water deeper (direction > 0) or shallower (direction < 0).
+
+	Calm and depth fade out towards the edge of the brush, walls have a hard edge.
+*/
+#version 450 core
+
+#define TOOL_CALM  1
+#define TOOL_WALL  2
+#define TOOL_DEPTH 3
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, r32f)    uniform image2D damping;    // see accel.glsl
+layout (binding = 2, r8)      uniform image2D obstacles;  // see obstacles.glsl
+layout (binding = 3, r32f)    uniform image2D bathymetry; // see accel.glsl
+
+uniform int   tool;
+uniform vec2  center;             // brush center, in pixels
+uniform float radius;             // brush radius, in pixels
+uniform float direction   = 1.0;  // left (1) or right (-1) mouse button
+uniform float calm_rate   = 0.01; // damping change per frame at the brush center
+uniform float max_damping = 1.0;  // stays well within the stability limit of the time step
+uniform float depth_rate  = 0.02; // relative depth change per frame at the brush center
+uniform float min_depth   = 0.05; // see MIN_DEPTH in main.rs
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+
+	float dist = distance(vec2(xy), center);
+	if (dist >= radius){
+		return;
+	}
+	float x = dist / (0.5 * radius);
+	float e = exp(-x*x);
+
+	switch (tool){
+	case TOOL_CALM:
+		float g = imageLoad(damping, xy).r + direction * calm_rate * e;
+		imageStore(damping, xy, vec4(clamp(g, 0.0, max_damping)));
+		break;
+	case TOOL_WALL:
+		imageStore(obstacles, xy, vec4(direction > 0.0 ? 1.0 : 0.0));
+		break;
+	case TOOL_DEPTH:
+		float d = imageLoad(bathymetry, xy).r + direction * depth_rate * e;
+		imageStore(bathymetry, xy, vec4(clamp(d, min_depth, 1.0)));
+		break;
+	}
+}
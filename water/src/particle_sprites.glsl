@@ -0,0 +1,81 @@
+/*
+	Draw the floating particles (particles.rs) as sprites:
+	leaves, in autumn colors with a darker midrib, into the surface layer,
+	and twigs into the debris layer. water.frag shows both layers.
+
+	One invocation per particle, drawing its bounding box.
+	Sprites crossing a periodic boundary (boundary.glsl) wrap around, others are cut off.
+*/
+#version 450 core
+
+layout (local_size_x = 64) in;
+
+layout (binding = 0, rgba8) uniform writeonly image2D surface;
+layout (binding = 1, rgba8) uniform writeonly image2D debris;
+
+// See particles.rs.
+struct Particle {
+	vec2  pos;
+	vec2  vel;
+	float angle;
+	float spin;
+	float size;
+	float kind;
+};
+
+layout (std430, binding = 3) readonly buffer Particles {
+	Particle particles[];
+};
+
+uniform int num_particles;
+
+const vec3 leaf_colors[4] = vec3[](
+	vec3(0.35, 0.50, 0.15), // green
+	vec3(0.85, 0.70, 0.20), // yellow
+	vec3(0.80, 0.40, 0.10), // orange
+	vec3(0.55, 0.20, 0.10)  // red-brown
+);
+
+const vec3 twig_color = vec3(0.30, 0.20, 0.12);
+
+float random(float x){
+	return fract(sin(x * 12.9898) * 43758.5453);
+}
+
+void main(){
+	int i = int(gl_GlobalInvocationID.x);
+	if (i >= num_particles){
+		return;
+	}
+	Particle p = particles[i];
+	vec2 dir = vec2(cos(p.angle), sin(p.angle));
+	bool leaf = p.kind < 0.5;
+
+	float r = random(float(i));
+	vec3 color = leaf ? mix(leaf_colors[int(r * 3.0)], leaf_colors[int(r * 3.0) + 1], fract(r * 3.0)) : twig_color;
+
+	int extent = int(ceil(0.5 * p.size)) + 1;
+	for (int y = -extent; y <= extent; y++){
+		for (int x = -extent; x <= extent; x++){
+			ivec2 xy = ivec2(floor(p.pos)) + ivec2(x, y);
+			vec2 d = vec2(xy) + 0.5 - p.pos;
+			if (boundary == BOUNDARY_PERIODIC){
+				xy = boundary_cell(xy, imageSize(surface));
+			}
+			float u = dot(d, dir) / (0.5 * p.size);           // along, -1 .. 1
+			float v = abs(dot(d, vec2(-dir.y, dir.x)));        // across, in pixels
+			if (abs(u) >= 1.0){
+				continue;
+			}
+			if (leaf){
+				float half_width = 0.22 * p.size * (1.0 - u * u);
+				if (v < half_width){
+					float rib = v < 0.5 ? 0.7 : 1.0;
+					imageStore(surface, xy, vec4(color * rib, 1.0));
+				}
+			} else if (v < 0.8){
+				imageStore(debris, xy, vec4(color, 1.0));
+			}
+		}
+	}
+}
@@ -0,0 +1,119 @@
+/*
+	Move the floating particles (particles.rs) by one time step.
+
+	A particle is accelerated down the surface slope, like the water itself
+	(with the gravity of swe_flow.glsl), and dragged along with the flow velocity
+	u: the shallow water flow (zero for the wave models) plus the background current:
+
+		dv/dt = -g grad(surface) + drag (u - v)
+
+	Sampled where the particle is, not at a fixed cell, the slope makes particles
+	bob back and forth on passing waves with a slow net drift along (Stokes drift).
+	Particles spin with the flow, and bounce off walls (obstacles.glsl) and window edges.
+	Particles stuck in a wall (painted over, or scattered there) are moved to a random spot.
+*/
+#version 450 core
+
+layout (local_size_x = 64) in;
+
+layout (binding = 0, r32f)  uniform readonly image2D height;  // surface height
+layout (binding = 1, rg32f) uniform readonly image2D current; // see current.rs
+layout (binding = 0)       uniform sampler2D flow;          // shallow water flow, see swe_flow.glsl
+
+// See particles.rs.
+struct Particle {
+	vec2  pos;   // in pixels
+	vec2  vel;   // in pixels per unit of time
+	float angle; // radians
+	float spin;  // radians per unit of time
+	float size;  // length, in pixels
+	float kind;  // 0: leaf, 1: debris
+};
+
+layout (std430, binding = 3) buffer Particles {
+	Particle particles[];
+};
+
+uniform int   num_particles;
+uniform float dt;
+uniform float gravity = 0.025;  // see swe_flow.glsl
+uniform float drag = 0.05;      // per unit of time
+uniform bool  use_flow = false; // follow the shallow water flow
+uniform int   rand_seed = 0;    // to be changed at every invocation
+
+// Surface height at cell xy, walls take the height h0 (as in normal.glsl).
+float load_height(ivec2 xy, float h0){
+	ivec2 c = boundary_cell(xy, imageSize(height));
+	return is_wall(c) ? h0 : imageLoad(height, c).r;
+}
+
+// Surface slope at position p (in pixels), bilinearly interpolated.
+vec2 slope(vec2 p){
+	vec2 f = p - 0.5;
+	ivec2 c = ivec2(floor(f));
+	vec2 t = fract(f);
+	vec2 g[4];
+	for (int i = 0; i < 4; i++){
+		ivec2 xy = c + ivec2(i & 1, i >> 1);
+		float h0 = load_height(xy, 0.0);
+		g[i] = 0.5 * vec2(load_height(xy + ivec2(1, 0), h0) - load_height(xy - ivec2(1, 0), h0),
+		                  load_height(xy + ivec2(0, 1), h0) - load_height(xy - ivec2(0, 1), h0));
+	}
+	return mix(mix(g[0], g[1], t.x), mix(g[2], g[3], t.x), t.y);
+}
+
+// Flow velocity at position p (in pixels), from the staggered velocities of swe_flow.glsl.
+vec2 flow_velocity(vec2 p){
+	vec2 size = vec2(textureSize(flow, 0));
+	float u = texture(flow, (p - vec2(0.5, 0.0)) / size).x;
+	float v = texture(flow, (p - vec2(0.0, 0.5)) / size).y;
+	return vec2(u, v);
+}
+
+float random(float x){
+	return fract(sin(float(rand_seed) * 1.618 + x * 12.9898) * 43758.5453);
+}
+
+void main(){
+	int i = int(gl_GlobalInvocationID.x);
+	if (i >= num_particles){
+		return;
+	}
+	Particle p = particles[i];
+	vec2 size = vec2(imageSize(height));
+
+	if (is_wall(ivec2(p.pos))){
+		p.pos = vec2(random(float(i)), random(float(i) + 0.5)) * size;
+		p.vel = vec2(0.0);
+		particles[i] = p;
+		return;
+	}
+
+	vec2 u = (use_flow ? flow_velocity(p.pos) : vec2(0.0)) + imageLoad(current, ivec2(p.pos)).xy;
+	p.vel += dt * (-gravity * slope(p.pos) + drag * (u - p.vel));
+	p.vel = clamp(p.vel, -1.0 / dt, 1.0 / dt); // at most a cell per step, not to jump through walls
+
+	if (use_flow){
+		// half the vorticity of the flow
+		float curl = (flow_velocity(p.pos + vec2(1.0, 0.0)).y - flow_velocity(p.pos - vec2(1.0, 0.0)).y)
+		           - (flow_velocity(p.pos + vec2(0.0, 1.0)).x - flow_velocity(p.pos - vec2(0.0, 1.0)).x);
+		p.spin += dt * drag * (0.25 * curl - p.spin);
+	}
+	p.angle += dt * p.spin;
+
+	vec2 next = p.pos + dt * p.vel;
+	if (boundary == BOUNDARY_PERIODIC){
+		next = mod(next, size);
+	} else {
+		bvec2 out_ = bvec2(next.x < 0.0 || next.x >= size.x, next.y < 0.0 || next.y >= size.y);
+		p.vel = mix(p.vel, -p.vel, out_);
+		next = mix(next, p.pos, out_);
+	}
+	if (is_wall(ivec2(next))){
+		p.vel = -0.5 * p.vel;
+	} else {
+		p.pos = next;
+	}
+
+	particles[i] = p;
+}
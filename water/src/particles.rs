@@ -0,0 +1,161 @@
+/*
+	Floating particles: leaves on the surface and waterlogged debris drifting just below,
+	to make the water motion readable.
+
+	Particles live in a shader storage buffer. Every time step, particles.glsl
+	accelerates them down the surface slope and drags them along with the flow
+	(of the shallow water model) and the background current (current.rs),
+	so they bob on passing waves and ride the currents.
+
+	Every frame, particle_sprites.glsl draws them as small sprites into two textures,
+	one for the surface and one for the debris layer. water.frag samples the surface
+	layer where the eye ray meets the water, lit and glinting with the surface normal,
+	and the debris layer along the refracted ray, like the floor.
+*/
+use crate::boundary::Boundary;
+use crate::shaders::*;
+use gl::*;
+use gl_obj::*;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+/// Fraction of the particles that are debris instead of leaves.
+const DEBRIS_FRACTION: f32 = 0.3;
+
+/// Compute shader work group size, as in particles.glsl and particle_sprites.glsl.
+const GROUP_SIZE: u32 = 64;
+
+/// Largest initial spin, in radians per unit of time.
+const MAX_SPIN: f32 = 0.05;
+
+/// A particle, laid out as in particles.glsl and particle_sprites.glsl.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+struct Particle {
+	x: f32,
+	y: f32,
+	vx: f32,
+	vy: f32,
+	angle: f32, // radians
+	spin: f32,  // radians per unit of time
+	size: f32,  // length, in pixels
+	kind: f32,  // 0: leaf, 1: debris
+}
+
+pub struct Particles {
+	p_move: Program,
+	p_sprites: Program,
+	buffer: Buffer,
+	surface: Texture,
+	debris: Texture,
+	grid: uvec2,
+	count: usize,
+	size: f32,
+	seed: u64,
+}
+
+impl Particles {
+	/// `count` particles, about `size` pixels long, scattered over a grid of given size and boundary.
+	/// Runs with the same seed get the same particles.
+	pub fn new(grid: uvec2, boundary: Boundary, count: usize, size: f32, seed: u64) -> Result<Self, String> {
+		let sprites = || boundary.wrap(Texture::new2d(RGBA8, grid).filter_linear().clear_image(0, gl::RGBA, gl::UNSIGNED_BYTE, &[0u8; 4]));
+		let s = Self {
+			p_move: link(&[shader!(COMPUTE_SHADER, "particles.glsl", "boundary.glsl", "obstacles.glsl")?])?,
+			p_sprites: link(&[shader!(COMPUTE_SHADER, "particle_sprites.glsl", "boundary.glsl")?])?,
+			buffer: Buffer::create().storage(&vec![Particle::default(); count.max(1)], gl::DYNAMIC_STORAGE_BIT),
+			surface: sprites(),
+			debris: sprites(),
+			grid,
+			count,
+			size,
+			seed,
+		};
+		s.p_move.set1i("boundary", boundary as i32);
+		s.p_sprites.set1i("boundary", boundary as i32);
+		s.scatter();
+		Ok(s)
+	}
+
+	/// Scatter the particles at random over the grid, at rest.
+	/// Particles landing on walls are moved by particles.glsl.
+	pub fn scatter(&self) {
+		let grid = self.grid;
+		if self.count == 0 {
+			return;
+		}
+		let mut rng = Pcg32::seed_from_u64(self.seed);
+		let particles: Vec<Particle> = (0..self.count)
+			.map(|_| Particle {
+				x: rng.gen::<f32>() * grid.0 as f32,
+				y: rng.gen::<f32>() * grid.1 as f32,
+				vx: 0.0,
+				vy: 0.0,
+				angle: rng.gen::<f32>() * std::f32::consts::TAU,
+				spin: rng.gen_range(-MAX_SPIN..MAX_SPIN),
+				size: self.size * rng.gen_range(0.6..1.2),
+				kind: if rng.gen::<f32>() < DEBRIS_FRACTION { 1.0 } else { 0.0 },
+			})
+			.collect();
+		self.buffer.sub_data(0, &particles);
+	}
+
+	/// Set the time step, the gravity (as in swe_flow.glsl), the drag towards the flow
+	/// and whether there is a flow to follow (the shallow water model).
+	pub fn set_motion(&self, dt: f32, gravity: f32, drag: f32, use_flow: bool) {
+		self.p_move //
+			.set1f("dt", dt)
+			.set1f("gravity", gravity)
+			.set1f("drag", drag)
+			.set1i("use_flow", use_flow as i32);
+	}
+
+	/// Move the particles by one time step over the `surface` height,
+	/// with the shallow water `flow` and the background `current`.
+	pub fn advance(&self, surface: Texture, flow: Texture, current: Texture, obstacles: Texture, rand_seed: i32) {
+		if self.count == 0 {
+			return;
+		}
+		self.p_move.set1i("num_particles", self.count as i32).set1i("rand_seed", rand_seed);
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, 3);
+		surface.bind_image_unit(0, READ_ONLY);
+		current.bind_image_unit(1, READ_ONLY);
+		flow.bind_texture_unit(0);
+		obstacles.bind_image_unit(crate::OBSTACLES_UNIT, READ_ONLY);
+		self.p_move.compute_and_sync(self.global_size())
+	}
+
+	/// Draw the sprites of the surface and debris layers.
+	pub fn draw_sprites(&self) {
+		let zero = [0u8; 4];
+		self.surface.clear_image(0, gl::RGBA, gl::UNSIGNED_BYTE, &zero);
+		self.debris.clear_image(0, gl::RGBA, gl::UNSIGNED_BYTE, &zero);
+		if self.count == 0 {
+			return;
+		}
+		self.p_sprites.set1i("num_particles", self.count as i32);
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, 3);
+		self.surface.bind_image_unit(0, WRITE_ONLY);
+		self.debris.bind_image_unit(1, WRITE_ONLY);
+		self.p_sprites.compute_and_sync(self.global_size())
+	}
+
+	/// Sprites floating on the surface (rgb: color, alpha: coverage).
+	pub fn surface(&self) -> Texture {
+		self.surface
+	}
+
+	/// Sprites drifting below the surface (rgb: color, alpha: coverage).
+	pub fn debris(&self) -> Texture {
+		self.debris
+	}
+
+	pub fn len(&self) -> usize {
+		self.count
+	}
+
+	/// One invocation per particle, rounded up to whole work groups.
+	fn global_size(&self) -> uvec3 {
+		uvec3((self.count as u32).div_ceil(GROUP_SIZE) * GROUP_SIZE, 1, 1)
+	}
+}
@@ -15,18 +15,22 @@
 	The random seed is supposed to change on each invocation,
 	to avoid static noise patterns in the output.
 
+	Photons leaving the grid wrap around (periodic boundary),
+	are mirrored (reflective boundary), or are lost (see boundary.glsl).
+
 */
 #version 450 core
 
 layout (local_size_x = 16, local_size_y = 16) in;
 
-layout(binding = 0)          uniform sampler2D normals; // water surface normals
-layout(binding = 1, rgba8ui) uniform uimage2D  photons; // output added here
+layout(binding = 0)          uniform sampler2D normals;    // water surface normals
+layout(binding = 1, rgba8ui) uniform uimage2D  photons;    // output added here
+layout(binding = 2)          uniform sampler2D bathymetry; // relative depth, scales `depth`
 
 uniform vec3  light_dir = normalize(vec3(0.03, 0.01, -1.0));  // sign??
 uniform float eta = 1.33;          // refractive index @ green
 uniform float dispersion = 0.01;   // delta refractive index @ cyan
-uniform float depth = 2.0;         // water - floor distance
+uniform float depth = 2.0;         // water - floor distance, where bathymetry is 1
 uniform int   rand_seed = 0;       // to be changed at every invocation
 
 // Colors represented as int,
@@ -46,11 +50,14 @@ uniform int   rand_seed = 0;       // to be changed at every invocation
 // Photons with varying colors (ranging from RED to PURPLE)
 // can be mapped with slightly differnt refractive indices
 // to achive dispersion (rainbow effect).
-void map_photon(vec2 start, vec3 n, float eta, uint col){
+void map_photon(vec2 start, vec3 n, float d, float eta, uint col){
 	ivec2 size = imageSize(photons);
 	vec3 refr = refract(light_dir, n, eta);
-	vec2 hit = start + depth * refr.xy;
-	ivec2 xy = ivec2(hit * size);
+	vec2 hit = start + d * refr.xy;
+	ivec2 xy = ivec2(floor(hit * size));
+	if (boundary == BOUNDARY_PERIODIC || boundary == BOUNDARY_REFLECTIVE) {
+		xy = boundary_cell(xy, size);
+	}
 	imageAtomicAdd(photons, xy, col);
 }
 
@@ -72,10 +79,13 @@ void main() {
 	vec2 start = pos + vec2(r, s) / size;
 	vec3 n = texture(normals, start).xyz;
 
-	map_photon(start, n, eta - 2.0*dispersion, RED);
-	map_photon(start, n, eta - 1.0*dispersion, YELLOW);
-	map_photon(start, n, eta - 0.0*dispersion, GREEN);
-	map_photon(start, n, eta + 1.0*dispersion, CYAN);
-	map_photon(start, n, eta + 2.0*dispersion, BLUE);
-	map_photon(start, n, eta + 3.0*dispersion, PURPLE);
+	// Approximation: the floor is assumed flat at the depth below the start position.
+	float d = depth * texture(bathymetry, start).r;
+
+	map_photon(start, n, d, eta - 2.0*dispersion, RED);
+	map_photon(start, n, d, eta - 1.0*dispersion, YELLOW);
+	map_photon(start, n, d, eta - 0.0*dispersion, GREEN);
+	map_photon(start, n, d, eta + 1.0*dispersion, CYAN);
+	map_photon(start, n, d, eta + 2.0*dispersion, BLUE);
+	map_photon(start, n, d, eta + 3.0*dispersion, PURPLE);
 }
@@ -0,0 +1,157 @@
+/*
+	Pointers (mouse cursor, touch points) that disturb the water.
+
+	Each pointer becomes a disturbance source,
+	uploaded to a shader storage buffer read by apply_mouse.glsl.
+
+	A moving pointer leaves a continuous wake, like a finger dragged through water:
+	the time steps of a frame sweep the disturbance along the pointer's path during that frame,
+	with a strength proportional to the pointer speed. A pointer held still does not disturb the water.
+*/
+use gl_obj::*;
+use std::collections::HashMap;
+
+/// Maximum number of simultaneous pointers,
+/// more are ignored.
+pub const MAX_POINTERS: usize = 16;
+
+/// Pointer speed (pixels per time step) giving the nominal disturbance strength.
+const WAKE_SPEED: f32 = 1.0;
+
+/// Limits the strength of very fast pointers, relative to the nominal strength.
+const MAX_WAKE_GAIN: f32 = 4.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PointerId {
+	Mouse,
+	Touch(u64),
+}
+
+/// A disturbance source, laid out as in apply_mouse.glsl:
+/// a line segment from (x0, y0) to (x1, y1), or a point if both are equal.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Source {
+	pub x0: f32,
+	pub y0: f32,
+	pub x1: f32,
+	pub y1: f32,
+	pub pow: f32,
+	pub rad: f32,
+	pub vx: f32,
+	pub vy: f32,
+}
+
+impl Source {
+	/// A source at rest at (x, y).
+	pub fn point(x: f32, y: f32, pow: f32, rad: f32) -> Self {
+		Self::segment((x, y), (x, y), pow, rad)
+	}
+
+	/// A source at rest along the line segment from a to b.
+	pub fn segment(a: (f32, f32), b: (f32, f32), pow: f32, rad: f32) -> Self {
+		Self {
+			x0: a.0,
+			y0: a.1,
+			x1: b.0,
+			y1: b.1,
+			pow,
+			rad,
+			vx: 0.0,
+			vy: 0.0,
+		}
+	}
+}
+
+#[derive(Default)]
+struct Pointer {
+	from: (f32, f32), // position at the start of the frame
+	to: (f32, f32),   // latest position
+	pow: f32,
+	placed: bool, // false until the first move
+}
+
+pub struct Pointers {
+	pointers: HashMap<PointerId, Pointer>,
+	buffer: Buffer,
+	radius: f32,
+}
+
+impl Pointers {
+	/// No pointers yet, new ones get disturbance radius `radius`.
+	pub fn new(radius: f32) -> Self {
+		Self {
+			pointers: HashMap::new(),
+			buffer: Buffer::create().storage(&[Source::default(); MAX_POINTERS], gl::DYNAMIC_STORAGE_BIT),
+			radius,
+		}
+	}
+
+	/// Move pointer `id`, adding it if needed.
+	pub fn move_to(&mut self, id: PointerId, x: f32, y: f32) {
+		let p = self.pointers.entry(id).or_default();
+		if !p.placed {
+			p.from = (x, y);
+			p.placed = true;
+		}
+		p.to = (x, y);
+	}
+
+	/// Set the disturbance strength of pointer `id`, adding it if needed.
+	pub fn set_pow(&mut self, id: PointerId, pow: f32) {
+		self.pointers.entry(id).or_default().pow = pow;
+	}
+
+	/// Change the disturbance radius of all pointers.
+	pub fn set_radius(&mut self, radius: f32) {
+		self.radius = radius;
+	}
+
+	pub fn remove(&mut self, id: PointerId) {
+		self.pointers.remove(&id);
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.pointers.is_empty()
+	}
+
+	/// Upload the sources for time step `i` out of `n` in this frame:
+	/// the i-th part of each pointer's path. `dt` is the time step.
+	/// Returns the number of sources.
+	pub fn sweep(&self, i: u32, n: u32, dt: f32) -> usize {
+		let sources: Vec<Source> = self
+			.pointers
+			.values()
+			.filter(|p| p.placed)
+			.take(MAX_POINTERS)
+			.map(|p| {
+				let lerp = |t: f32| (p.from.0 + t * (p.to.0 - p.from.0), p.from.1 + t * (p.to.1 - p.from.1));
+				let (a, b) = (lerp((i as f32) / (n as f32)), lerp(((i + 1) as f32) / (n as f32)));
+				let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+				let speed = f32::sqrt(dx * dx + dy * dy);
+				let gain = f32::min(speed / WAKE_SPEED, MAX_WAKE_GAIN);
+				Source {
+					vx: dx / dt,
+					vy: dy / dt,
+					..Source::segment(a, b, gain * p.pow, self.radius)
+				}
+			})
+			.collect();
+		if !sources.is_empty() {
+			self.buffer.sub_data(0, &sources);
+		}
+		sources.len()
+	}
+
+	/// Start a new frame: pointers will sweep from where they are now.
+	pub fn end_frame(&mut self) {
+		for p in self.pointers.values_mut() {
+			p.from = p.to;
+		}
+	}
+
+	/// Bind the source buffer to shader storage block `index`.
+	pub fn bind(&self, index: u32) {
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, index)
+	}
+}
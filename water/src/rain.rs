@@ -0,0 +1,72 @@
+/*
+	Rain: random drops falling on the water.
+
+	Each time step, a random number of drops (on average `rate`) falls at random positions,
+	with radii uniformly distributed between `min_radius` and `max_radius`.
+	Drops are disturbance sources like pointers (pointers.rs), applied once by apply_mouse.glsl.
+
+	Drops come from a seeded PCG generator, so runs with the same seed rain the same.
+*/
+use crate::pointers::Source;
+use gl_obj::*;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+/// Maximum number of drops per time step,
+/// more are ignored.
+pub const MAX_DROPS: usize = 256;
+
+pub struct Rain {
+	rng: Pcg32,
+	buffer: Buffer,
+	size: uvec2,
+	rate: f32,
+	min_radius: f32,
+	max_radius: f32,
+	strength: f32,
+}
+
+impl Rain {
+	/// Rain on a grid of given size, with on average `rate` drops per time step.
+	pub fn new(size: uvec2, rate: f32, min_radius: f32, max_radius: f32, strength: f32, seed: u64) -> Self {
+		Self {
+			rng: Pcg32::seed_from_u64(seed),
+			buffer: Buffer::create().storage(&[Source::default(); MAX_DROPS], gl::DYNAMIC_STORAGE_BIT),
+			size,
+			rate,
+			min_radius,
+			max_radius: f32::max(min_radius, max_radius),
+			strength,
+		}
+	}
+
+	pub fn is_raining(&self) -> bool {
+		self.rate > 0.0
+	}
+
+	/// Let the drops of one time step fall: upload them to the buffer and return how many there are.
+	pub fn fall(&mut self) -> usize {
+		// the fractional part of the rate becomes the chance of one more drop
+		let extra = self.rng.gen::<f32>() < self.rate.fract();
+		let n = usize::min(self.rate as usize + extra as usize, MAX_DROPS);
+		if n == 0 {
+			return 0;
+		}
+		let drops: Vec<Source> = (0..n)
+			.map(|_| {
+				let x = self.rng.gen_range(0.0..self.size.0 as f32);
+				let y = self.rng.gen_range(0.0..self.size.1 as f32);
+				let rad = self.rng.gen_range(self.min_radius..=self.max_radius);
+				Source::point(x, y, self.strength, rad)
+			})
+			.collect();
+		self.buffer.sub_data(0, &drops);
+		n
+	}
+
+	/// Bind the drop buffer to shader storage block `index`.
+	pub fn bind(&self, index: u32) {
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, index)
+	}
+}
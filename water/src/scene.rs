@@ -0,0 +1,239 @@
+/*
+	Scripted wave sources, read from a scene file or --source options.
+
+	One source per line: a kind, its geometry, then optional `key=value` parameters:
+
+		# kind   geometry          parameters
+		point    x y               amplitude=0.2 frequency=0.02 phase=0 radius=6 vx=0 vy=0
+		line     x0 y0 x1 y1       amplitude=0.2 frequency=0.02
+		paddle   left              frequency=0.01
+
+	  * point:  a point source at (x, y), moving with velocity (vx, vy) (cells per unit of time).
+	  * line:   a line source from (x0, y0) to (x1, y1).
+	  * paddle: a wave maker along a window edge (left, right, top or bottom), sending plane waves.
+
+	Sources push the surface down by amplitude * sin(2π frequency t + phase) every time step,
+	spread over `radius` cells around the point or line (apply_mouse.glsl).
+	Frequencies are in cycles per unit of time. Waves travel 0.5 cells per unit of time,
+	so the wavelength is 0.5 / frequency cells.
+*/
+use crate::pointers::Source;
+use crate::spec::*;
+use gl_obj::*;
+use std::f32::consts::PI;
+use std::fs;
+
+/// Maximum number of sources, more are an error.
+pub const MAX_OSCILLATORS: usize = 64;
+
+/// An oscillating line segment source (a point source has a == b).
+#[derive(Clone, Copy, Debug)]
+pub struct Oscillator {
+	a: (f32, f32),
+	b: (f32, f32),
+	velocity: (f32, f32),
+	amplitude: f32,
+	frequency: f32,
+	phase: f32,
+	radius: f32,
+}
+
+impl Oscillator {
+	/// The source at time `t`.
+	fn at(&self, t: f64) -> Source {
+		// phase in f64: f32 time would jitter after hours of running
+		let cycles = ((self.frequency as f64) * t).fract() as f32;
+		let t = t as f32;
+		let (dx, dy) = (self.velocity.0 * t, self.velocity.1 * t);
+		let pow = self.amplitude * f32::sin(2.0 * PI * cycles + self.phase);
+		Source {
+			vx: self.velocity.0,
+			vy: self.velocity.1,
+			..Source::segment((self.a.0 + dx, self.a.1 + dy), (self.b.0 + dx, self.b.1 + dy), pow, self.radius)
+		}
+	}
+}
+
+pub struct Scene {
+	oscillators: Vec<Oscillator>,
+	buffer: Buffer,
+	size: uvec2,
+}
+
+impl Scene {
+	/// A scene without sources, on a grid of given size (needed to place paddles).
+	pub fn new(size: uvec2) -> Self {
+		Self {
+			oscillators: Vec::new(),
+			buffer: Buffer::create().storage(&[Source::default(); MAX_OSCILLATORS], gl::DYNAMIC_STORAGE_BIT),
+			size,
+		}
+	}
+
+	/// Add sources from a scene file.
+	pub fn load(&mut self, fname: &str) -> Result<(), String> {
+		let text = fs::read_to_string(fname).map_err(|e| format!("{}: {}", fname, e))?;
+		self.extend(&text).map_err(|e| format!("{}: {}", fname, e))
+	}
+
+	/// Add sources from text (see top of file for the format).
+	pub fn extend(&mut self, text: &str) -> Result<(), String> {
+		let more = parse_scene(text, self.size, self.oscillators.len())?;
+		self.oscillators.extend(more);
+		Ok(())
+	}
+
+	pub fn len(&self) -> usize {
+		self.oscillators.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.oscillators.is_empty()
+	}
+
+	/// Upload the sources as they are at time `t`.
+	pub fn upload(&self, t: f64) {
+		let sources: Vec<Source> = self.oscillators.iter().map(|o| o.at(t)).collect();
+		if !sources.is_empty() {
+			self.buffer.sub_data(0, &sources);
+		}
+	}
+
+	/// Bind the source buffer to shader storage block `index`.
+	pub fn bind(&self, index: u32) {
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, index)
+	}
+}
+
+/// Sources from text (see top of file for the format) on a grid of given size,
+/// to be added to `have` sources already there.
+fn parse_scene(text: &str, size: uvec2, have: usize) -> Result<Vec<Oscillator>, String> {
+	let mut oscillators = Vec::new();
+	for (i, line) in text.lines().enumerate() {
+		let line = line.split('#').next().unwrap().trim();
+		if line.is_empty() {
+			continue;
+		}
+		let osc = parse_source(line, size).map_err(|e| format!("line {}: {}", i + 1, e))?;
+		if have + oscillators.len() == MAX_OSCILLATORS {
+			return Err(format!("line {}: more than {} sources", i + 1, MAX_OSCILLATORS));
+		}
+		oscillators.push(osc);
+	}
+	Ok(oscillators)
+}
+
+/// One source from a line of text, without comment.
+fn parse_source(line: &str, size: uvec2) -> Result<Oscillator, String> {
+	let spec = Spec::parse(line)?;
+	let (kind, geometry) = spec.words.split_first().ok_or("want a source: point, line or paddle")?;
+	let want = |n: usize| {
+		if geometry.len() == n {
+			Ok(())
+		} else {
+			Err(format!("`{}`: want {} coordinates, have {}", kind, n, geometry.len()))
+		}
+	};
+	let num = |i: usize| number(geometry[i]);
+
+	let (w, h) = (size.0 as f32, size.1 as f32);
+	let mut osc = Oscillator {
+		a: (0.0, 0.0),
+		b: (0.0, 0.0),
+		velocity: (0.0, 0.0),
+		amplitude: 0.2,
+		frequency: 0.02,
+		phase: 0.0,
+		radius: 6.0,
+	};
+	match *kind {
+		"point" => {
+			want(2)?;
+			osc.a = (num(0)?, num(1)?);
+			osc.b = osc.a;
+		}
+		"line" => {
+			want(4)?;
+			osc.a = (num(0)?, num(1)?);
+			osc.b = (num(2)?, num(3)?);
+		}
+		"paddle" => {
+			want(1)?;
+			let (a, b) = match geometry[0] {
+				"left" => ((0.0, 0.0), (0.0, h)),
+				"right" => ((w - 1.0, 0.0), (w - 1.0, h)),
+				"top" => ((0.0, 0.0), (w, 0.0)),
+				"bottom" => ((0.0, h - 1.0), (w, h - 1.0)),
+				edge => return Err(format!("unknown edge `{}`, options: left, right, top, bottom", edge)),
+			};
+			osc.a = a;
+			osc.b = b;
+		}
+		kind => return Err(format!("unknown source `{}`, options: point, line, paddle", kind)),
+	}
+
+	for &(key, value) in &spec.params {
+		match key {
+			"amplitude" => osc.amplitude = value,
+			"frequency" => osc.frequency = value,
+			"phase" => osc.phase = value,
+			"radius" => osc.radius = value,
+			"vx" => osc.velocity.0 = value,
+			"vy" => osc.velocity.1 = value,
+			_ => return Err(format!("unknown parameter `{}`, options: amplitude, frequency, phase, radius, vx, vy", key)),
+		}
+	}
+	Ok(osc)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SIZE: uvec2 = uvec2(200, 100);
+
+	#[test]
+	fn parse_valid() {
+		let text = "
+			# a comment
+			point 10 20 amplitude=0.5 vx=1  # trailing comment
+
+			line 0 0 50 60 frequency=0.1 phase=1.5
+			paddle bottom
+		";
+		let osc = parse_scene(text, SIZE, 0).unwrap();
+		assert_eq!(osc.len(), 3);
+		assert_eq!((osc[0].a, osc[0].b), ((10.0, 20.0), (10.0, 20.0)));
+		assert_eq!((osc[0].amplitude, osc[0].velocity, osc[0].frequency), (0.5, (1.0, 0.0), 0.02));
+		assert_eq!((osc[1].a, osc[1].b), ((0.0, 0.0), (50.0, 60.0)));
+		assert_eq!((osc[1].frequency, osc[1].phase), (0.1, 1.5));
+		assert_eq!((osc[2].a, osc[2].b), ((0.0, 99.0), (200.0, 99.0)));
+	}
+
+	#[test]
+	fn parse_errors() {
+		let err = |text: &str| parse_scene(text, SIZE, 0).unwrap_err();
+		assert_eq!(
+			err("point 1 2\npoint 1 2 speed=3"),
+			"line 2: unknown parameter `speed`, options: amplitude, frequency, phase, radius, vx, vy"
+		);
+		assert_eq!(err("point 1 2 amplitude=x"), "line 1: `amplitude=x`: not a number");
+		assert_eq!(err("point 1"), "line 1: `point`: want 2 coordinates, have 1");
+		assert_eq!(err("wave 1 2"), "line 1: unknown source `wave`, options: point, line, paddle");
+		assert_eq!(err("paddle middle"), "line 1: unknown edge `middle`, options: left, right, top, bottom");
+	}
+
+	#[test]
+	fn parse_too_many() {
+		let text = "point 1 2\n".repeat(MAX_OSCILLATORS);
+		assert_eq!(parse_scene(&text, SIZE, 0).unwrap().len(), MAX_OSCILLATORS);
+		assert_eq!(
+			parse_scene(&(text.clone() + "point 1 2"), SIZE, 0).unwrap_err(),
+			format!("line {}: more than {} sources", MAX_OSCILLATORS + 1, MAX_OSCILLATORS)
+		);
+		assert_eq!(
+			parse_scene("point 1 2", SIZE, MAX_OSCILLATORS).unwrap_err(),
+			format!("line 1: more than {} sources", MAX_OSCILLATORS)
+		);
+	}
+}
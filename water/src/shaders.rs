@@ -0,0 +1,39 @@
+use gl_obj::*;
+
+/// Compile a shader from a source file in this directory,
+/// optionally inserting other source files (e.g. "boundary.glsl") after its `#version` line.
+/// Errors mention the file name.
+macro_rules! shader {
+	($shader_type:expr, $file:literal $(, $include:literal)*) => {
+		Shader::try_new($shader_type, &crate::shaders::with_includes(include_str!($file), &[$(include_str!($include)),*]))
+			.map_err(|e| format!("compile shader {}: {}", $file, e))
+	};
+}
+
+/// Link shaders into a program.
+pub fn link(shaders: &[Shader]) -> Result<Program, String> {
+	Program::try_new(shaders).map_err(|e| format!("link program: {}", e))
+}
+
+/// Insert `includes` into shader source `src`, right after its `#version` line.
+/// A `#line` directive keeps line numbers in error messages pointing into `src`.
+pub fn with_includes(src: &str, includes: &[&str]) -> String {
+	if includes.is_empty() {
+		return src.into();
+	}
+	let mut out = String::with_capacity(src.len() + includes.iter().map(|s| s.len()).sum::<usize>());
+	let mut done = false;
+	for (i, line) in src.lines().enumerate() {
+		out.push_str(line);
+		out.push('\n');
+		if !done && line.trim_start().starts_with("#version") {
+			for inc in includes {
+				out.push_str(inc);
+				out.push('\n');
+			}
+			out.push_str(&format!("#line {}\n", i + 2));
+			done = true;
+		}
+	}
+	out
+}
@@ -0,0 +1,193 @@
+/*
+	Snapshots: the full simulation state in a file, to resume interesting moments,
+	share them, or reproduce a bug exactly.
+
+	A snapshot holds the command line options of the run (its parameters), the time step counter,
+	the water fields and the maps painted with the mouse tools. Saved and loaded with a key
+	(to and from the --snapshot file), or loaded at startup with --load.
+
+	File layout, little endian:
+
+		magic       8 bytes "WATERSNP"
+		version     u32
+		options     u32 count, then each option as u32 length + UTF-8 bytes
+		width       u32
+		height      u32
+		time step   u64
+		pos         f32 per cell, row by row
+		vel         f32 per cell
+		flow        4 x f32 per cell (see swe_flow.glsl)
+		obstacles   u8 per cell (see obstacles.glsl)
+		bathymetry  f32 per cell
+		damping     f32 per cell
+
+	Floating particles, bodies, boats and rain (its random generator) are not saved,
+	so runs with any of them are not saved either: they would not resume as they were.
+*/
+use gl_obj::*;
+use std::convert::TryInto;
+use std::fs;
+
+const MAGIC: &[u8; 8] = b"WATERSNP";
+
+/// File format version, to be incremented on every change of the layout.
+const VERSION: u32 = 1;
+
+pub struct Snapshot {
+	/// Command line options of the run, without the program name.
+	pub args: Vec<String>,
+	pub size: uvec2,
+	pub time_step: u64,
+	pub pos: Vec<f32>,
+	pub vel: Vec<f32>,
+	pub flow: Vec<[f32; 4]>,
+	pub obstacles: Vec<u8>,
+	pub bathymetry: Vec<f32>,
+	pub damping: Vec<f32>,
+}
+
+impl Snapshot {
+	pub fn save(&self, fname: &str) -> Result<(), String> {
+		fs::write(fname, self.encode()).map_err(|e| format!("save snapshot {}: {}", fname, e))
+	}
+
+	pub fn load(fname: &str) -> Result<Self, String> {
+		let data = fs::read(fname).map_err(|e| format!("load snapshot {}: {}", fname, e))?;
+		Self::decode(&data).map_err(|e| format!("load snapshot {}: {}", fname, e))
+	}
+
+	fn encode(&self) -> Vec<u8> {
+		let mut b = Vec::new();
+		b.extend_from_slice(MAGIC);
+		b.extend_from_slice(&VERSION.to_le_bytes());
+		b.extend_from_slice(&(self.args.len() as u32).to_le_bytes());
+		for arg in &self.args {
+			b.extend_from_slice(&(arg.len() as u32).to_le_bytes());
+			b.extend_from_slice(arg.as_bytes());
+		}
+		b.extend_from_slice(&self.size.0.to_le_bytes());
+		b.extend_from_slice(&self.size.1.to_le_bytes());
+		b.extend_from_slice(&self.time_step.to_le_bytes());
+		let floats = |b: &mut Vec<u8>, v: &[f32]| v.iter().for_each(|x| b.extend_from_slice(&x.to_le_bytes()));
+		floats(&mut b, &self.pos);
+		floats(&mut b, &self.vel);
+		self.flow.iter().for_each(|f| floats(&mut b, f));
+		b.extend_from_slice(&self.obstacles);
+		floats(&mut b, &self.bathymetry);
+		floats(&mut b, &self.damping);
+		b
+	}
+
+	fn decode(data: &[u8]) -> Result<Self, String> {
+		let mut r = Reader(data);
+		if r.take(MAGIC.len())? != MAGIC {
+			return Err("not a snapshot file".into());
+		}
+		let version = r.u32()?;
+		if version != VERSION {
+			return Err(format!("unsupported version {}, want {}", version, VERSION));
+		}
+		let mut args = Vec::new();
+		for _ in 0..r.u32()? {
+			let n = r.u32()? as usize;
+			args.push(String::from_utf8(r.take(n)?.to_vec()).map_err(|e| e.to_string())?);
+		}
+		let size = uvec2(r.u32()?, r.u32()?);
+		let time_step = r.u64()?;
+		let n = (size.0 as usize) * (size.1 as usize);
+		let s = Self {
+			args,
+			size,
+			time_step,
+			pos: r.floats(n)?,
+			vel: r.floats(n)?,
+			flow: r.floats(n.saturating_mul(4))?.chunks(4).map(|c| [c[0], c[1], c[2], c[3]]).collect(),
+			obstacles: r.take(n)?.to_vec(),
+			bathymetry: r.floats(n)?,
+			damping: r.floats(n)?,
+		};
+		if !r.0.is_empty() {
+			return Err(format!("{} unexpected bytes at the end", r.0.len()));
+		}
+		Ok(s)
+	}
+}
+
+/// Reads little endian values from the front of a byte slice.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+	fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+		if n > self.0.len() {
+			return Err("unexpected end of file".into());
+		}
+		let (head, tail) = self.0.split_at(n);
+		self.0 = tail;
+		Ok(head)
+	}
+
+	fn u32(&mut self) -> Result<u32, String> {
+		Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+	}
+
+	fn u64(&mut self) -> Result<u64, String> {
+		Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+	}
+
+	fn floats(&mut self, n: usize) -> Result<Vec<f32>, String> {
+		Ok(self.take(n.saturating_mul(4))?.chunks(4).map(|c| f32::from_le_bytes(c.try_into().unwrap())).collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn small() -> Snapshot {
+		let n = 6;
+		let ramp = |k: f32| (0..n).map(|i| k * i as f32).collect::<Vec<_>>();
+		Snapshot {
+			args: vec!["--width".into(), "3".into(), "--model".into(), "swe".into()],
+			size: uvec2(3, 2),
+			time_step: 1234,
+			pos: ramp(0.5),
+			vel: ramp(-0.25),
+			flow: (0..n).map(|i| [i as f32, 1.0, 2.0, -3.0]).collect(),
+			obstacles: vec![0, 255, 0, 128, 0, 1],
+			bathymetry: ramp(2.0),
+			damping: ramp(0.125),
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		let s = small();
+		let t = Snapshot::decode(&s.encode()).unwrap();
+		assert_eq!(t.args, s.args);
+		assert_eq!((t.size.0, t.size.1, t.time_step), (s.size.0, s.size.1, s.time_step));
+		assert_eq!((t.pos, t.vel, t.flow), (s.pos, s.vel, s.flow));
+		assert_eq!((t.obstacles, t.bathymetry, t.damping), (s.obstacles, s.bathymetry, s.damping));
+	}
+
+	#[test]
+	fn rejects_bad_files() {
+		let data = small().encode();
+		let err = |data: &[u8]| Snapshot::decode(data).err().unwrap();
+
+		for n in [0, 4, 12, data.len() - 1].iter() {
+			assert_eq!(err(&data[..*n]), "unexpected end of file", "truncated to {} bytes", n);
+		}
+
+		let mut bad = data.clone();
+		bad[0] = b'X';
+		assert_eq!(err(&bad), "not a snapshot file");
+
+		let mut bad = data.clone();
+		bad[8..12].copy_from_slice(&(VERSION + 1).to_le_bytes());
+		assert_eq!(err(&bad), format!("unsupported version {}, want {}", VERSION + 1, VERSION));
+
+		let mut bad = data;
+		bad.extend_from_slice(&[0, 0, 0]);
+		assert_eq!(err(&bad), "3 unexpected bytes at the end");
+	}
+}
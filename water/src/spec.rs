@@ -0,0 +1,51 @@
+/*
+	One-line specs of scene sources (scene.rs), --init shapes (init.rs), --boat paths (boats.rs)
+	and --body options (bodies.rs): words separated by spaces, optional `key=value` numbers among them.
+
+		point 10 20 amplitude=0.5 frequency=0.02
+*/
+
+/// A one-line spec, split into its plain words and its `key=value` parameters, each in order.
+pub struct Spec<'a> {
+	pub words: Vec<&'a str>,
+	pub params: Vec<(&'a str, f32)>,
+}
+
+impl<'a> Spec<'a> {
+	pub fn parse(text: &'a str) -> Result<Self, String> {
+		let mut spec = Self {
+			words: Vec::new(),
+			params: Vec::new(),
+		};
+		for word in text.split_whitespace() {
+			match word.find('=') {
+				None => spec.words.push(word),
+				Some(i) => {
+					let value = word[i + 1..].parse().map_err(|_| format!("`{}`: not a number", word))?;
+					spec.params.push((&word[..i], value));
+				}
+			}
+		}
+		Ok(spec)
+	}
+}
+
+/// Parse a word as a number.
+pub fn number(word: &str) -> Result<f32, String> {
+	word.parse().map_err(|_| format!("`{}`: not a number", word))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse() {
+		let spec = Spec::parse("  line 1 2\tamplitude=0.5 3 phase=-1 ").unwrap();
+		assert_eq!(spec.words, ["line", "1", "2", "3"]);
+		assert_eq!(spec.params, [("amplitude", 0.5), ("phase", -1.0)]);
+		assert_eq!(Spec::parse("point radius=").err().unwrap(), "`radius=`: not a number");
+		assert_eq!(number("1e3"), Ok(1000.0));
+		assert_eq!(number("x"), Err("`x`: not a number".to_string()));
+	}
+}
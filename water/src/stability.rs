@@ -0,0 +1,73 @@
+/*
+	Guarding against numerical blow-up.
+
+	At startup, time steps beyond the stability (CFL) limit are split into substeps.
+	At runtime, health.glsl reduces the height field on the GPU to its largest magnitude
+	and the number of NaN/Inf cells, so that a blown-up simulation can be reset or clamped.
+*/
+use crate::shaders::*;
+use gl::*;
+use gl_obj::*;
+
+/// Fraction of the stability limit actually used when substepping.
+const CFL_SAFETY: f32 = 0.9;
+
+/// Number of substeps needed to keep time step `dt` within the stability limit `stable_dt`.
+pub fn substeps(dt: f32, stable_dt: f32) -> u32 {
+	u32::max(1, (dt / (CFL_SAFETY * stable_dt)).ceil() as u32)
+}
+
+/// Result of a health check, laid out as in health.glsl.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Health {
+	max_height: u32,
+	pub non_finite: u32,
+}
+
+impl Health {
+	/// Largest magnitude of the finite heights.
+	pub fn max_height(&self) -> f32 {
+		f32::from_bits(self.max_height)
+	}
+}
+
+pub struct HealthCheck {
+	p_health: Program,
+	p_clamp: Program,
+	buffer: Buffer,
+}
+
+impl HealthCheck {
+	pub fn new() -> Result<Self, String> {
+		Ok(Self {
+			p_health: link(&[shader!(COMPUTE_SHADER, "health.glsl")?])?,
+			p_clamp: link(&[shader!(COMPUTE_SHADER, "clamp_height.glsl")?])?,
+			buffer: Buffer::create().storage(&[Health::default()], gl::DYNAMIC_STORAGE_BIT),
+		})
+	}
+
+	/// Check height field `pos`. Reads back from the GPU, so don't call this every frame.
+	pub fn check(&self, pos: Texture) -> Health {
+		self.buffer.sub_data(0, &[Health::default()]);
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+		pos.bind_image_unit(0, READ_ONLY);
+		exec(self.p_health, pos);
+		let mut health = [Health::default()];
+		self.buffer.get_sub_data(0, &mut health);
+		health[0]
+	}
+
+	/// Clamp heights to `max_height`, stopping clamped cells.
+	pub fn clamp(&self, pos: Texture, vel: Texture, max_height: f32) {
+		self.p_clamp.set1f("max_height", max_height);
+		pos.bind_image_unit(0, READ_WRITE);
+		vel.bind_image_unit(1, READ_WRITE);
+		exec(self.p_clamp, pos);
+	}
+}
+
+fn exec(p: Program, pos: Texture) {
+	let xy = pos.size();
+	p.compute_and_sync(uvec3(xy.0, xy.1, 1))
+}
@@ -0,0 +1,474 @@
+/*
+	Discrete Laplacian stencils for the wave model (accel.glsl).
+
+	A stencil is a list of taps (dx, dy, w), approximating
+
+		laplacian(z)[x, y] = Σ w * (z[x+dx, y+dy] - z[x, y])
+
+	The taps are uploaded as uniforms, so accel.glsl and the CPU reference below
+	use the very same weights.
+
+	The deep water stencil is not a Laplacian, but a wide kernel whose Fourier symbol is
+	proportional to |k| instead of k², giving the dispersion ω² = g |k| of deep water waves
+	over a range of wavelengths (Tessendorf, Interactive Water Surfaces, Game Programming Gems 4 (2004)).
+*/
+use gl_obj::*;
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// Maximum number of taps, the size of the `taps` array in accel.glsl.
+pub const MAX_TAPS: usize = 96;
+
+/// Radius of the deep water kernel, in cells.
+const DEEP_RADIUS: i32 = 5;
+
+/// Deep water waves of this wavelength (cells) travel as fast as the waves of the other stencils.
+const DEEP_WAVELENGTH: f32 = 16.0;
+
+/// Longest wavelength (cells) with deep water dispersion.
+const DEEP_MAX_WAVELENGTH: f32 = 24.0;
+
+/// Smooths the deep water kernel by suppressing waves near the grid scale: exp(-DEEP_SMOOTHING k²).
+const DEEP_SMOOTHING: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stencil {
+	/// Standard 5-point stencil. Short waves travel faster along the diagonals,
+	/// making small circular ripples square-ish.
+	Five,
+	/// 9-point stencil whose error is isotropic to leading order
+	/// (Patra & Karttunen, Numer. Methods Partial Differ. Equ. 22, 936 (2006)).
+	Nine,
+	/// Fourth-order accurate 9-point cross of radius 2. Fourth order on the 13-point diamond
+	/// of radius 2 requires zero weight on its diagonals, which leaves this cross.
+	Fourth,
+	/// Deep water gravity waves, see top of file: long waves outrun short ones,
+	/// so that moving boats trail a Kelvin wake (boats.rs).
+	Deep,
+}
+
+impl Stencil {
+	/// Neighbour offsets and weights, see top of file.
+	pub fn taps(self) -> Vec<(i32, i32, f32)> {
+		let axial = |r: i32, w: f32| vec![(r, 0, w), (-r, 0, w), (0, r, w), (0, -r, w)];
+		let diagonal = |w: f32| vec![(1, 1, w), (-1, 1, w), (1, -1, w), (-1, -1, w)];
+		match self {
+			Stencil::Five => axial(1, 1.0),
+			Stencil::Nine => [axial(1, 2.0 / 3.0), diagonal(1.0 / 6.0)].concat(),
+			Stencil::Fourth => [axial(1, 4.0 / 3.0), axial(2, -1.0 / 12.0)].concat(),
+			Stencil::Deep => deep_taps(),
+		}
+	}
+
+	/// Largest stable verlet time step (verlet.glsl) in the deepest water,
+	/// with surface tension coefficient `tension` (accel.glsl).
+	/// Leapfrog is stable as long as dt * ω < 2 for the fastest grid mode,
+	/// with ω² = 0.25 * Σ w (1 - cos(k·(dx, dy))) + tension * l5²,
+	/// where l5 is the same sum for the 5-point stencil of laplacian.glsl.
+	pub fn stable_dt(self, tension: f32) -> f32 {
+		let symbol = |taps: &[(i32, i32, f32)], kx: f32, ky: f32| -> f32 { taps.iter().map(|&(dx, dy, w)| w * (1.0 - f32::cos(kx * (dx as f32) + ky * (dy as f32)))).sum() };
+		let (taps, five) = (self.taps(), Stencil::Five.taps());
+		let n = 64;
+		let mut max: f32 = 0.0;
+		for i in 0..=n {
+			for j in 0..=n {
+				let (kx, ky) = (PI * (i as f32) / (n as f32), PI * (j as f32) / (n as f32));
+				let w2 = 0.25 * symbol(&taps, kx, ky) + tension * symbol(&five, kx, ky).powi(2);
+				max = f32::max(max, w2);
+			}
+		}
+		2.0 / f32::sqrt(max)
+	}
+
+	/// Set the `taps` and `num_taps` uniforms of accel.glsl.
+	pub fn set_uniforms(self, p: Program) {
+		let taps = self.taps();
+		assert!(taps.len() <= MAX_TAPS);
+		p.set1i("num_taps", taps.len() as i32);
+		for (i, (dx, dy, w)) in taps.into_iter().enumerate() {
+			p.set3f(&format!("taps[{}]", i), dx as f32, dy as f32, w);
+		}
+	}
+}
+
+impl FromStr for Stencil {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"5" => Ok(Stencil::Five),
+			"9" => Ok(Stencil::Nine),
+			"4th" => Ok(Stencil::Fourth),
+			"deep" => Ok(Stencil::Deep),
+			_ => Err(format!("unknown stencil `{}`, options: 5, 9, 4th, deep", s)),
+		}
+	}
+}
+
+impl fmt::Display for Stencil {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			Stencil::Five => "5",
+			Stencil::Nine => "9",
+			Stencil::Fourth => "4th",
+			Stencil::Deep => "deep",
+		};
+		f.write_str(s)
+	}
+}
+
+/// Taps of the deep water kernel: one weight per orbit of offsets under the symmetries of the grid,
+/// least squares fitted so that ω² = 0.25 * Σ w (1 - cos(k·(dx, dy))) ≈ g F(k),
+/// with F(k) = |k| exp(-DEEP_SMOOTHING k²), for wavelengths up to DEEP_MAX_WAVELENGTH cells in all directions.
+/// Longer waves are less dispersive: no finite kernel has a symbol ∝ |k| at small k.
+fn deep_taps() -> Vec<(i32, i32, f32)> {
+	let f = |k: f64| k * f64::exp(-(DEEP_SMOOTHING as f64) * k * k);
+	// g such that ω² = g F(k) = (0.5 k)² at the reference wavelength.
+	let k_ref = 2.0 * std::f64::consts::PI / (DEEP_WAVELENGTH as f64);
+	let g = 0.25 * k_ref * k_ref / f(k_ref);
+
+	// offsets by orbit, identified by (min(|dx|, |dy|), max(|dx|, |dy|)).
+	let mut orbits = std::collections::BTreeMap::new();
+	for dy in -DEEP_RADIUS..=DEEP_RADIUS {
+		for dx in -DEEP_RADIUS..=DEEP_RADIUS {
+			if (dx, dy) != (0, 0) && dx * dx + dy * dy <= DEEP_RADIUS * DEEP_RADIUS {
+				let key = (i32::min(dx.abs(), dy.abs()), i32::max(dx.abs(), dy.abs()));
+				orbits.entry(key).or_insert_with(Vec::new).push((dx, dy));
+			}
+		}
+	}
+	let orbits: Vec<Vec<(i32, i32)>> = orbits.into_values().collect();
+	let m = orbits.len();
+
+	// normal equations of the fit, relative errors sampled logarithmically
+	// from the longest wavelength to the grid scale, in directions between 0 and 45 degrees.
+	let mut a = vec![vec![0.0f64; m]; m];
+	let mut b = vec![0.0f64; m];
+	let k_min = 2.0 * std::f64::consts::PI / (DEEP_MAX_WAVELENGTH as f64);
+	for i in 0..40 {
+		let k = k_min * f64::powf(std::f64::consts::PI / k_min, (i as f64) / 39.0);
+		for angle in (0..=45).step_by(9) {
+			let (kx, ky) = (k * (angle as f64).to_radians().cos(), k * (angle as f64).to_radians().sin());
+			let row: Vec<f64> = orbits
+				.iter()
+				.map(|o| o.iter().map(|&(dx, dy)| 0.25 * (1.0 - f64::cos(kx * dx as f64 + ky * dy as f64))).sum::<f64>() / f(k))
+				.collect();
+			for r in 0..m {
+				for c in 0..m {
+					a[r][c] += row[r] * row[c];
+				}
+				b[r] += row[r] * g;
+			}
+		}
+	}
+
+	// Gauss-Jordan elimination with partial pivoting.
+	for i in 0..m {
+		let p = (i..m).max_by(|&x, &y| a[x][i].abs().partial_cmp(&a[y][i].abs()).unwrap()).unwrap();
+		a.swap(i, p);
+		b.swap(i, p);
+		for r in 0..m {
+			if r != i {
+				let c = a[r][i] / a[i][i];
+				let pivot = a[i].clone();
+				for (x, y) in a[r].iter_mut().zip(pivot) {
+					*x -= c * y;
+				}
+				b[r] -= c * b[i];
+			}
+		}
+	}
+
+	let mut taps = Vec::new();
+	for (i, o) in orbits.iter().enumerate() {
+		let w = (b[i] / a[i][i]) as f32;
+		taps.extend(o.iter().map(|&(dx, dy)| (dx, dy, w)));
+	}
+	taps
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// CPU reference of accel.glsl (+ laplacian.glsl) + verlet.glsl on an n x n grid:
+	/// uniform depth, no damping, fixed or periodic boundary.
+	struct Reference {
+		n: i32,
+		pos: Vec<f32>,
+		vel: Vec<f32>,
+		walls: Vec<bool>,
+		taps: Vec<(i32, i32, f32)>,
+		tension: f32,
+		periodic: bool,
+	}
+
+	impl Reference {
+		fn new(n: i32, stencil: Stencil) -> Self {
+			let len = (n * n) as usize;
+			Self {
+				n,
+				pos: vec![0.0; len],
+				vel: vec![0.0; len],
+				walls: vec![false; len],
+				taps: stencil.taps(),
+				tension: 0.0,
+				periodic: false,
+			}
+		}
+
+		fn z(&self, x: i32, y: i32) -> f32 {
+			self.at(&self.pos, x, y)
+		}
+
+		/// Index of cell (x, y), None outside the grid (fixed boundary).
+		fn index(&self, x: i32, y: i32) -> Option<usize> {
+			let n = self.n;
+			let (x, y) = if self.periodic { (x.rem_euclid(n), y.rem_euclid(n)) } else { (x, y) };
+			if x < 0 || y < 0 || x >= n || y >= n {
+				return None;
+			}
+			Some((y * n + x) as usize)
+		}
+
+		/// Value of grid `v` at (x, y), zero outside.
+		fn at(&self, v: &[f32], x: i32, y: i32) -> f32 {
+			self.index(x, y).map_or(0.0, |i| v[i])
+		}
+
+		fn is_wall(&self, x: i32, y: i32) -> bool {
+			self.index(x, y).is_some_and(|i| self.walls[i])
+		}
+
+		/// Whether there is a wall on the line from (x, y) to (x + dx, y + dy), as in accel.glsl.
+		fn blocked(&self, x: i32, y: i32, dx: i32, dy: i32) -> bool {
+			let n = i32::max(dx.abs(), dy.abs());
+			self.is_wall(x + dx, y + dy)
+				|| (1..n).any(|i| {
+					let (fx, fy) = ((dx * i) as f32 / n as f32, (dy * i) as f32 / n as f32);
+					let near = |f: f32| ((f + 0.5).floor() as i32, (f - 0.5).ceil() as i32);
+					let ((x0, x1), (y0, y1)) = (near(fx), near(fy));
+					self.is_wall(x + x0, y + y0) || self.is_wall(x + x1, y + y1)
+				})
+		}
+
+		/// 5-point Laplacian, as in laplacian.glsl.
+		fn laplacian(&self, v: &[f32]) -> Vec<f32> {
+			let n = self.n;
+			let mut l = vec![0.0; v.len()];
+			for y in 0..n {
+				for x in 0..n {
+					let z0 = self.at(v, x, y);
+					l[(y * n + x) as usize] = Stencil::Five.taps().iter().map(|&(dx, dy, w)| w * (self.at(v, x + dx, y + dy) - z0)).sum();
+				}
+			}
+			l
+		}
+
+		fn step(&mut self, dt: f32) {
+			let mut acc = vec![0.0; self.pos.len()];
+			let walls = self.walls.contains(&true); // without any, no tap is blocked
+			for y in 0..self.n {
+				for x in 0..self.n {
+					if self.is_wall(x, y) {
+						continue;
+					}
+					let z0 = self.z(x, y);
+					let flux = |&(dx, dy, w): &(i32, i32, f32)| if walls && self.blocked(x, y, dx, dy) { 0.0 } else { w * (self.z(x + dx, y + dy) - z0) };
+					acc[(y * self.n + x) as usize] = 0.25 * self.taps.iter().map(flux).sum::<f32>();
+				}
+			}
+			if self.tension != 0.0 {
+				let bilapl = self.laplacian(&self.laplacian(&self.pos));
+				for (a, b) in acc.iter_mut().zip(bilapl) {
+					*a -= self.tension * b;
+				}
+			}
+			for ((p, v), a) in self.pos.iter_mut().zip(self.vel.iter_mut()).zip(acc) {
+				*v += a * dt;
+				*p += *v * dt;
+			}
+		}
+
+		/// Bilinearly interpolated height at (x, y).
+		fn sample(&self, x: f32, y: f32) -> f32 {
+			let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+			let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+			let lerp = |a: f32, b: f32, t: f32| a + t * (b - a);
+			lerp(lerp(self.z(x0, y0), self.z(x0 + 1, y0), fx), lerp(self.z(x0, y0 + 1), self.z(x0 + 1, y0 + 1), fx), fy)
+		}
+	}
+
+	/// Relative spread (max - min) / mean of the radius of the strongest crest
+	/// of a ring expanding from a narrow point source, over directions between 0 and 45 degrees.
+	fn ring_anisotropy(stencil: Stencil) -> f32 {
+		let n = 121;
+		let c = (n / 2) as f32;
+		let mut s = Reference::new(n, stencil);
+		for y in 0..n {
+			for x in 0..n {
+				let r2 = (x as f32 - c).powi(2) + (y as f32 - c).powi(2);
+				s.pos[(y * n + x) as usize] = f32::exp(-r2 / 3.0);
+			}
+		}
+		for _ in 0..150 {
+			s.step(0.5);
+		}
+
+		let radii: Vec<f32> = (0..=9)
+			.map(|i| {
+				let angle = (i as f32) * 5.0 * PI / 180.0;
+				let crest = |r: f32| s.sample(c + r * angle.cos(), c + r * angle.sin()).abs();
+				let dr = 0.25;
+				let samples = (30..210).map(|j| (j as f32) * dr);
+				let r = samples.max_by(|&a, &b| crest(a).partial_cmp(&crest(b)).unwrap()).unwrap();
+				// refine to the top of a parabola through the neighbouring samples
+				let (lo, mid, hi) = (crest(r - dr), crest(r), crest(r + dr));
+				r + 0.5 * dr * (lo - hi) / (lo - 2.0 * mid + hi)
+			})
+			.collect();
+		let min = radii.iter().cloned().fold(f32::INFINITY, f32::min);
+		let max = radii.iter().cloned().fold(0.0, f32::max);
+		let mean = radii.iter().sum::<f32>() / (radii.len() as f32);
+		(max - min) / mean
+	}
+
+	#[test]
+	fn taps_sum_to_laplacian() {
+		// Exact for quadratics: laplacian(x² + y²) = 4.
+		for stencil in [Stencil::Five, Stencil::Nine, Stencil::Fourth] {
+			let lapl: f32 = stencil.taps().iter().map(|&(dx, dy, w)| w * ((dx * dx + dy * dy) as f32)).sum();
+			assert!((lapl - 4.0).abs() < 1e-5, "{}: {}", stencil, lapl);
+		}
+	}
+
+	#[test]
+	fn stable_dt() {
+		assert!((Stencil::Five.stable_dt(0.0) - f32::sqrt(2.0)).abs() < 1e-5);
+
+		// Pseudo-random noise excites the fastest modes, which blow up beyond the limit.
+		let max_height = |stencil: Stencil, tension: f32, dt: f32| {
+			let mut s = Reference::new(24, stencil);
+			s.tension = tension;
+			let mut seed = 1u32;
+			for p in &mut s.pos {
+				seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+				*p = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+			}
+			for _ in 0..200 {
+				s.step(dt);
+			}
+			s.pos.iter().fold(0.0, |m: f32, p| if p.is_nan() { f32::INFINITY } else { m.max(p.abs()) })
+		};
+		for stencil in [Stencil::Five, Stencil::Nine, Stencil::Fourth, Stencil::Deep] {
+			for tension in [0.0, 0.5] {
+				let dt = stencil.stable_dt(tension);
+				let (below, beyond) = (max_height(stencil, tension, 0.95 * dt), max_height(stencil, tension, 1.05 * dt));
+				assert!(below < 10.0, "stencil {}, tension {}: max height {} below the stability limit", stencil, tension, below);
+				assert!(beyond > 1e3, "stencil {}, tension {}: max height {} beyond the stability limit", stencil, tension, beyond);
+			}
+		}
+	}
+
+	#[test]
+	fn deep_water_dispersion() {
+		// ω² ∝ |k| in all directions: phase speed ∝ 1 / sqrt(wavelength).
+		let taps = Stencil::Deep.taps();
+		assert!(taps.len() <= MAX_TAPS);
+		let omega2 = |kx: f32, ky: f32| 0.25 * taps.iter().map(|&(dx, dy, w)| w * (1.0 - f32::cos(kx * dx as f32 + ky * dy as f32))).sum::<f32>();
+		let k_ref = 2.0 * PI / DEEP_WAVELENGTH;
+		for wavelength in [3.0, 6.0, 12.0, DEEP_WAVELENGTH] {
+			let k = 2.0 * PI / wavelength;
+			let want = 0.25 * k_ref * k_ref * (k / k_ref) * f32::exp(-DEEP_SMOOTHING * (k * k - k_ref * k_ref));
+			let along = omega2(k, 0.0);
+			let diagonal = omega2(k / f32::sqrt(2.0), k / f32::sqrt(2.0));
+			assert!((along / want - 1.0).abs() < 0.2, "wavelength {}: ω² {} along the axis, want {}", wavelength, along, want);
+			assert!(
+				(diagonal / want - 1.0).abs() < 0.2,
+				"wavelength {}: ω² {} along the diagonal, want {}",
+				wavelength,
+				diagonal,
+				want
+			);
+		}
+	}
+
+	#[test]
+	fn walls_block_deep_water() {
+		// A wall one cell thick across the grid: no tap of the wide deep water stencil reaches beyond it.
+		let (n, c) = (31, 15);
+		let mut s = Reference::new(n, Stencil::Deep);
+		for y in 0..n {
+			s.walls[(y * n + c) as usize] = true;
+			for x in 0..c {
+				let r2 = ((x - c + 6).pow(2) + (y - c).pow(2)) as f32;
+				s.pos[(y * n + x) as usize] = f32::exp(-r2 / 3.0);
+			}
+		}
+		let dt = 0.5 * Stencil::Deep.stable_dt(0.0);
+		for _ in 0..40 {
+			s.step(dt);
+		}
+		let max_height = |xs: std::ops::Range<i32>| xs.flat_map(|x| (0..n).map(move |y| (x, y))).map(|(x, y)| s.z(x, y).abs()).fold(0.0, f32::max);
+		let (before, beyond) = (max_height(c - 1..c), max_height(c + 1..n));
+		assert!(before > 0.01, "max height next to the wall: {}", before);
+		assert!(beyond == 0.0, "max height beyond the wall: {}", beyond);
+	}
+
+	/// Distance from the center (x, y) = (c, c) along the x axis beyond which
+	/// the height is below 1% of the largest height.
+	fn front_radius(s: &Reference, c: i32) -> i32 {
+		let max = (c..s.n).map(|x| s.z(x, c).abs()).fold(0.0, f32::max);
+		(c..s.n).rev().find(|&x| s.z(x, c).abs() > 0.01 * max).unwrap() - c
+	}
+
+	#[test]
+	fn tension_disperses() {
+		// A drop spreads at speed 0.5 without tension, short ripples outrun it with tension.
+		let (dt, steps) = (0.2, 100);
+		// A drop in the middle of an n x n grid, or moved by `shift` cells along both axes on a periodic grid.
+		let spread = |n: i32, tension: f32, periodic: bool, shift: i32| {
+			let c = n / 2;
+			let mut s = Reference::new(n, Stencil::Five);
+			s.tension = tension;
+			s.periodic = periodic;
+			for y in 0..n {
+				for x in 0..n {
+					let r2 = ((x - c).pow(2) + (y - c).pow(2)) as f32;
+					let i = s.index(x + shift, y + shift).unwrap();
+					s.pos[i] = f32::exp(-r2 / 3.0);
+				}
+			}
+			for _ in 0..steps {
+				s.step(dt);
+			}
+			s
+		};
+		let (n, c) = (61, 30);
+		let (still, rippled) = (front_radius(&spread(n, 0.0, false, 0), c), front_radius(&spread(n, 0.2, false, 0), c));
+		let source_radius = 8.0;
+		assert!((still as f32) < 0.5 * dt * (steps as f32) + source_radius, "front radius without tension: {}", still);
+		assert!((rippled as f32) > 1.5 * (still as f32), "front radius: {} without tension, {} with tension", still, rippled);
+
+		// On a periodic grid, a drop in the corner spreads across the edges as one in the middle, without a seam.
+		let (n, c) = (32, 16);
+		let (middle, corner) = (spread(n, 0.2, true, 0), spread(n, 0.2, true, -c));
+		let edge = (0..n).map(|y| middle.z(0, y).abs()).fold(0.0, f32::max);
+		let seam = (0..n)
+			.flat_map(|y| (0..n).map(move |x| (x, y)))
+			.map(|(x, y)| (middle.z(x, y) - corner.z(x - c, y - c)).abs())
+			.fold(0.0, f32::max);
+		assert!(edge > 1e-3, "ripples do not reach the edge: max height {}", edge);
+		assert!(seam < 1e-6, "max height difference between a drop in the middle and in the corner: {}", seam);
+	}
+
+	#[test]
+	fn ring_anisotropy_decreases() {
+		let five = ring_anisotropy(Stencil::Five);
+		let nine = ring_anisotropy(Stencil::Nine);
+		let fourth = ring_anisotropy(Stencil::Fourth);
+		assert!(nine < 0.5 * five, "ring anisotropy: 5-point {}, 9-point {}", five, nine);
+		assert!(fourth < 0.5 * five, "ring anisotropy: 5-point {}, fourth order {}", five, fourth);
+	}
+}
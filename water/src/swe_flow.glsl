@@ -0,0 +1,100 @@
+/*
+	Nonlinear shallow water equations, step 1: update the flow.
+
+	The water column height h and depth averaged velocity u obey
+
+		dh/dt + div(h u)   = 0
+		du/dt + (u.grad) u = -g grad(surface)
+
+	They are solved on a staggered grid: surface heights (pos) at cell centers,
+	velocities at cell faces. flow.x is the velocity through the face between
+	cells xy and xy + (1,0), flow.y through the face between xy and xy + (0,1).
+	Velocities are advected semi-Lagrangian, then accelerated by the surface slope.
+
+	The resulting mass fluxes (velocity times the column height upwind of the face)
+	are stored in flow.zw, swe_height.glsl moves the water accordingly.
+	Velocities are limited to max_courant cells per time step,
+	so that no cell can lose more water than it holds.
+
+	Surface pressure (of boats, boats.glsl), in units of height, adds to the surface in its gradient.
+
+	Surface heights are relative to the still water level, the column height is
+	h = still_depth * bathymetry + height. Gravity g = 0.25 / still_depth
+	gives small waves the same speed as accel.glsl.
+
+	Faces into walls (obstacles.glsl) and across the window edges are closed,
+	except for periodic boundaries (boundary.glsl).
+
+	https://en.wikipedia.org/wiki/Shallow_water_equations
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0)               uniform sampler2D flow_in;    // flow of the previous step
+layout (binding = 0, r32f)    uniform readonly  image2D height;
+layout (binding = 1, rgba32f) uniform writeonly image2D flow_out;
+layout (binding = 4, r32f)    uniform readonly  image2D pressure;   // see accel.glsl
+layout (binding = 5, r32f)    uniform readonly  image2D damping;    // see accel.glsl
+layout (binding = 6, r32f)    uniform readonly  image2D bathymetry;
+
+uniform float dt;
+uniform float still_depth = 10.0; // water depth where bathymetry is 1
+uniform float max_courant = 0.25; // velocity limit, in cells per time step
+
+// Water column height in cell xy, zero for dry cells.
+float column(ivec2 xy) {
+	return max(still_depth * imageLoad(bathymetry, xy).r + imageLoad(height, xy).r, 0.0);
+}
+
+// Surface height plus pressure in cell xy.
+float surface(ivec2 xy) {
+	return imageLoad(height, xy).r + imageLoad(pressure, xy).r;
+}
+
+// Velocity component c (0: x, 1: y) at position p (in cells), bilinearly interpolated.
+float sample_flow(vec2 p, int c) {
+	vec2 offset = c == 0 ? vec2(0.5, 0.0) : vec2(0.0, 0.5);
+	vec2 uv = (p - offset + 0.5) / vec2(textureSize(flow_in, 0));
+	return texture(flow_in, uv)[c];
+}
+
+// New velocity component c through the face between cell xy and xy + dir.
+float face_velocity(ivec2 xy, int c, ivec2 dir) {
+	ivec2 size = imageSize(height);
+	float gravity = 0.25 / still_depth;
+	ivec2 n = xy + dir;
+	if (any(greaterThanEqual(n, size))) {
+		if (boundary != BOUNDARY_PERIODIC) {
+			return 0.0;
+		}
+		n = boundary_cell(n, size);
+	}
+	if (is_wall(xy) || is_wall(n) || max(column(xy), column(n)) <= 0.0) {
+		return 0.0;
+	}
+
+	vec2 p = vec2(xy) + 0.5 * vec2(dir);
+	vec2 vel = vec2(sample_flow(p, 0), sample_flow(p, 1));
+	float u = sample_flow(p - dt * vel, c);
+
+	u -= dt * gravity * (surface(n) - surface(xy));
+	u -= dt * imageLoad(damping, xy).r * u;
+
+	float umax = max_courant / dt;
+	return clamp(u, -umax, umax);
+}
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+	ivec2 size = imageSize(height);
+
+	float u = face_velocity(xy, 0, ivec2(1, 0));
+	float v = face_velocity(xy, 1, ivec2(0, 1));
+
+	float h0 = column(xy);
+	float qx = u * (u > 0.0 ? h0 : column(boundary_cell(xy + ivec2(1, 0), size)));
+	float qy = v * (v > 0.0 ? h0 : column(boundary_cell(xy + ivec2(0, 1), size)));
+
+	imageStore(flow_out, xy, vec4(u, v, qx, qy));
+}
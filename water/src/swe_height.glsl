@@ -0,0 +1,42 @@
+/*
+	Nonlinear shallow water equations, step 2: move the water.
+
+	Applies the mass fluxes through the cell faces calculated by swe_flow.glsl:
+	what flows out of one cell flows into its neighbour, so the total amount of water is conserved.
+	Walls (obstacles.glsl) stay dry.
+*/
+#version 450 core
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+layout (binding = 0, r32f)    uniform          image2D height;
+layout (binding = 1, rgba32f) uniform readonly image2D flow;
+
+uniform float dt;
+
+// Mass fluxes (x, y) out of cell xy through its +x and +y faces.
+// Zero beyond the window edges, unless the boundary is periodic.
+vec2 load_flux(ivec2 xy) {
+	if (boundary == BOUNDARY_PERIODIC) {
+		xy = boundary_cell(xy, imageSize(flow));
+	}
+	return imageLoad(flow, xy).zw;
+}
+
+void main(){
+	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+
+	if (is_wall(xy)){
+		imageStore(height, xy, vec4(0.0));
+		return;
+	}
+
+	vec2 q = load_flux(xy);
+	float qx = load_flux(xy - ivec2(1, 0)).x;
+	float qy = load_flux(xy - ivec2(0, 1)).y;
+
+	float z = imageLoad(height, xy).r;
+	z -= dt * (q.x - qx + q.y - qy);
+
+	imageStore(height, xy, vec4(z, 0.0, 0.0, 0.0));
+}
@@ -4,8 +4,8 @@
 */
 #version 450 core
 
-in  vec2 vertex_pos;
-in  vec2 vertex_tex_coord;
+layout(location = 0) in vec2 vertex_pos;
+layout(location = 1) in vec2 vertex_tex_coord;
 out vec2 frag_tex_coord;
 
 void main() {
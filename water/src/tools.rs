@@ -0,0 +1,45 @@
+/*
+	Mouse tools: what the left and right mouse buttons do.
+
+	  * push:  push the water down (left) or pull it up (right) along the cursor path (pointers.rs).
+	  * calm:  add damping (left) or remove it (right) in the damping map.
+	  * wall:  paint walls (left) or erase them (right) in the obstacle mask.
+	  * depth: make the water deeper (left) or shallower (right).
+
+	Except for push, tools are applied by paint.glsl, once per frame while a button is held.
+	The scroll wheel changes the brush size, water.frag draws its outline.
+*/
+use std::fmt;
+
+/// Tools, numbered as in paint.glsl.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tool {
+	Push = 0,
+	Calm = 1,
+	Wall = 2,
+	Depth = 3,
+}
+
+impl Tool {
+	/// Brush outline color.
+	pub fn color(self) -> (f32, f32, f32) {
+		match self {
+			Tool::Push => (1.0, 1.0, 1.0),
+			Tool::Calm => (0.4, 0.7, 1.0),
+			Tool::Wall => (0.3, 0.3, 0.3),
+			Tool::Depth => (0.8, 0.6, 0.3),
+		}
+	}
+}
+
+impl fmt::Display for Tool {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			Tool::Push => "push",
+			Tool::Calm => "calm",
+			Tool::Wall => "wall",
+			Tool::Depth => "depth",
+		};
+		f.write_str(s)
+	}
+}
@@ -2,6 +2,8 @@
 	Verlet (leapfrog) integration:
 	update velocities and positions given accelartions.
 
+	Walls (obstacles.glsl) are held flat and at rest.
+
 	https://en.wikipedia.org/wiki/Leapfrog_integration
 */
 #version 450 core
@@ -24,6 +26,11 @@ void main(){
 	v = v + a * dt;
 	p = p + v * dt;
 
+	if (is_wall(xy)){
+		p = 0.0;
+		v = 0.0;
+	}
+
 	imageStore(pos, xy, vec4(p, 0.0, 0.0, 0.0));
 	imageStore(vel, xy, vec4(v, 0.0, 0.0, 0.0));
 }
\ No newline at end of file
@@ -8,6 +8,12 @@
 	  * refraction when looking through the water
 	  * a "floor" texture under the water
 	  * a pre-calculated photon map + ambient light illuminate the floor.
+	  * obstacles (walls) are shown as dry floor, without reflection or refraction.
+	  * floating particles (particles.rs): leaves on the surface, lit and glinting
+	    with the surface normal, and debris below, seen through the refraction.
+	  * floating bodies (bodies.rs): balls and tilting boxes, drawn larger when higher up.
+	  * boats (boats.rs), drawn on top of the water.
+	  * the outline of the mouse tool's brush (tools.rs).
 
 */
 #version 450 core
@@ -19,10 +25,17 @@ layout(binding = 0) uniform sampler2D  normal; // water surface normals
 layout(binding = 1) uniform sampler2D  sky;    // environment map
 layout(binding = 2) uniform sampler2D  floor;  // floor texture
 layout(binding = 3) uniform usampler2D photon; // photon map (see photon.glsl)
+layout(binding = 4) uniform sampler2D  obstacles; // > 0.5: wall (see obstacles.glsl)
+layout(binding = 5) uniform sampler2D  bathymetry; // relative depth, scales water_refraction_depth
+layout(binding = 6) uniform sampler2D  leaves;     // floating particles (see particle_sprites.glsl)
+layout(binding = 7) uniform sampler2D  debris;     // particles below the surface
 
 // refraction
 uniform float water_refraction       = 1.33; // water index of refraction
-uniform float water_refraction_depth = 0.2;  // water - floor distance
+uniform float water_refraction_depth = 0.2;  // water - floor distance, where bathymetry is 1
+
+// depth of the debris, relative to the floor
+uniform float debris_depth = 0.5;
 
 // reflection
 uniform float reflection_height   = 2.0;  // water - sky distance
@@ -34,18 +47,126 @@ uniform float ambient = 0.8;         // ambient light added to photon map.
 uniform float photon_strength = 0.2; // scales photon map intensity
 uniform vec3  light_dir = normalize(vec3(0.1, 0.2, 0.7));
 
+// brush outline
+uniform vec3 brush = vec3(0.0);       // center x, y and radius, in pixels. Radius 0: no outline
+uniform vec3 brush_color = vec3(1.0);
+
+// boats, see boats.glsl
+struct Hull {
+	vec2  pos;
+	vec2  dir;
+	float length;
+	float width;
+	float draft;
+	float pad;
+};
+
+layout (std430, binding = 2) readonly buffer Hulls {
+	Hull hulls[];
+};
+
+uniform int num_boats = 0;
+
+// floating bodies, see bodies.glsl
+struct Body {
+	vec2  pos;
+	vec2  dir;
+	vec2  tilt;
+	vec2  size;
+	float z;
+	float height;
+	float shape;
+	float pad;
+};
+
+layout (std430, binding = 4) readonly buffer Bodies {
+	Body bodies[];
+};
+
+uniform int   num_bodies = 0;
+uniform float body_perspective = 0.02; // apparent growth per unit of height
+uniform float body_tilt_shading = 8.0; // exaggerates the tilt of boxes in their shading
+
 // photon map normalization
 // photon.glsl sends ~4 photons per pixel
 // udecay.glsl decay causes an additional factor 2x
 // So divide by 8 to be normalized.
 #define PHOTON_NORM (8.0)     
 
+// Draw the boats over `color`: hulls with a pointed bow, a dark gunwale and a cabin.
+vec3 with_boats(vec3 color) {
+	vec2 size = vec2(textureSize(normal, 0));
+	vec2 pix = frag_tex_coord * size;
+	for (int i = 0; i < num_boats; i++){
+		Hull h = hulls[i];
+		vec2 r = pix - h.pos;
+		r -= size * round(r / size); // boats wrap around the edges
+		float u = dot(r, h.dir) / (0.5 * h.length);
+		float v = abs(dot(r, vec2(-h.dir.y, h.dir.x))) / (0.5 * h.width);
+		float half_width = u > 0.0 ? 1.0 - u * u : sqrt(max(1.0 - u * u, 0.0));
+		if (abs(u) < 1.0 && v < half_width){
+			bool gunwale = v > half_width - 0.25 || u < -1.0 + 0.08;
+			bool cabin = u > -0.5 && u < 0.1 && v < 0.5;
+			return gunwale ? vec3(0.35, 0.2, 0.1) : cabin ? vec3(0.75, 0.8, 0.85) : vec3(0.95, 0.93, 0.88);
+		}
+	}
+	return color;
+}
+
+// Draw the floating bodies over `color`: shaded red balls and wooden boxes with planks.
+vec3 with_bodies(vec3 color) {
+	vec2 size = vec2(textureSize(normal, 0));
+	vec2 pix = frag_tex_coord * size;
+	for (int i = 0; i < num_bodies; i++){
+		Body b = bodies[i];
+		vec2 r = pix - b.pos;
+		r -= size * round(r / size); // bodies on a periodic boundary wrap around the edges
+		r /= 1.0 + body_perspective * b.z;
+		if (b.shape < 0.5){
+			float q = dot(r, r) / (b.size.x * b.size.x);
+			if (q < 1.0){
+				vec3 n = normalize(vec3(r / b.size.x, sqrt(1.0 - q)));
+				float diffuse = max(dot(n, light_dir), 0.0);
+				float shine = pow(max(dot(reflect(-light_dir, n), vec3(0.0, 0.0, 1.0)), 0.0), 32.0);
+				return vec3(0.85, 0.2, 0.15) * (0.35 + 0.65 * diffuse) + 0.5 * shine;
+			}
+		} else {
+			vec2 uv = vec2(dot(r, b.dir), dot(r, vec2(-b.dir.y, b.dir.x)));
+			if (all(lessThan(abs(uv), b.size))){
+				vec3 n = normalize(vec3(-body_tilt_shading * b.tilt, 1.0));
+				bool edge = any(greaterThan(abs(uv), b.size - 1.5));
+				bool plank = fract(uv.y / 4.0) < 0.12;
+				vec3 wood = edge ? vec3(0.35, 0.22, 0.12) : plank ? vec3(0.5, 0.35, 0.2) : vec3(0.65, 0.48, 0.28);
+				return wood * (0.4 + 0.7 * max(dot(n, light_dir), 0.0));
+			}
+		}
+	}
+	return color;
+}
+
+// Draw the brush outline over `color`.
+vec4 with_brush(vec3 color) {
+	vec2 pix = frag_tex_coord * vec2(textureSize(normal, 0));
+	float ring = 1.0 - abs(distance(pix, brush.xy) - brush.z);
+	if (brush.z > 0.0 && ring > 0.0){
+		color = mix(color, brush_color, 0.7 * ring);
+	}
+	return vec4(color, 1.0);
+}
+
 void main() {
 
 	// initial ray
 	vec2 start = frag_tex_coord;
 	vec3 dir   = vec3(0.0, 0.0, -1.0);
 
+	// dry land
+	if (texture(obstacles, start).r > 0.5){
+		vec3 ph = texture(photon, start).rgb * (photon_strength / PHOTON_NORM);
+		output_color = with_brush(with_boats(with_bodies(texture(floor, start).rgb * (ambient + ph))));
+		return;
+	}
+
 	vec3   n = texture(normal, start).xyz;
 
 	// sky reflection
@@ -67,14 +188,21 @@ void main() {
 
 	// refraction
 	vec3 refr_dir   = refract(-dir, n, water_refraction);
-	float     depth = water_refraction_depth;
+	float     depth = water_refraction_depth * texture(bathymetry, start).r; // floor assumed flat around here
 	            hit = start + depth * refr_dir.xy;
 	vec3 ph = texture(photon, hit).rgb * (photon_strength / PHOTON_NORM);
 	vec3 refracted = texture(floor, hit).rgb * (ambient + ph);
 
-	output_color = vec4(
-		refracted +
-		sun +
-		reflection_strength * reflected ,
-		1.0);
+	// debris, lit by the average caustics, on the way to the floor
+	vec4 d = texture(debris, start + debris_depth * depth * refr_dir.xy);
+	refracted = mix(refracted, d.rgb * (ambient + photon_strength), d.a);
+
+	vec3 color = refracted + sun + reflection_strength * reflected;
+
+	// leaves cover the water, they tilt with the surface and catch some of the sun
+	vec4 leaf = texture(leaves, start);
+	vec3 leaf_color = leaf.rgb * (ambient + 0.5 * max(dot(n, light_dir), 0.0)) + 0.5 * sun;
+	color = mix(color, leaf_color, leaf.a);
+
+	output_color = with_brush(with_boats(with_bodies(color)));
 }
\ No newline at end of file